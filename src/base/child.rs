@@ -0,0 +1,133 @@
+//! 子进程的启动、等待和回收
+//!
+//! 钩子、健康检查和事件接收方的命令由调用方自己等待，回收线程只回收 [`track_child`] 记录的应用进程，
+//! 避免 `waitpid(-1)` 抢先回收这些命令导致调用方等待失败
+
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::io;
+use std::process::{Child, Command};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 通过shell执行 command 的命令，Unix下为 `sh -c`，Windows下为 `cmd /C`
+pub fn shell_command(command: &str) -> Command {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command);
+    cmd
+}
+
+/// 等待命令结束，退出码不为0时返回错误，超过 timeout 时结束命令并返回 TimedOut 错误
+pub fn wait_timeout(child: &mut Child, timeout: Duration) -> io::Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait()? {
+            Some(status) if status.success() => return Ok(()),
+            Some(status) => return Err(io::Error::other(t!("command.exited", status))),
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    t!("command.timed_out"),
+                ));
+            }
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+// 由pmr启动、尚未回收的应用进程
+static TRACKED: Lazy<Mutex<HashSet<u32>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// 子进程的退出状态，只有由当前进程启动的子进程才能获取
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExitStatus {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+}
+
+/// 记录由pmr启动的应用进程，退出后由 [`reap_children`] 回收
+pub fn track_child(pid: u32) {
+    tracked().insert(pid);
+}
+
+/// 调用方已经自己等待了子进程，不再由回收线程处理
+pub fn untrack_child(pid: u32) {
+    tracked().remove(&pid);
+}
+
+fn tracked() -> std::sync::MutexGuard<'static, HashSet<u32>> {
+    TRACKED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 回收已经退出的应用进程，避免产生僵尸进程，返回它们的PID和退出状态
+#[cfg(unix)]
+pub fn reap_children() -> Vec<(u32, ExitStatus)> {
+    let mut exits = Vec::new();
+    tracked().retain(|&pid| {
+        let mut status = 0;
+        match unsafe { libc::waitpid(pid as libc::pid_t, &mut status, libc::WNOHANG) } {
+            0 => true,
+            // 已经被其他地方回收，或者不是当前进程的子进程
+            r if r < 0 => false,
+            _ => {
+                let exit = if libc::WIFEXITED(status) {
+                    ExitStatus {
+                        code: Some(libc::WEXITSTATUS(status)),
+                        signal: None,
+                    }
+                } else {
+                    ExitStatus {
+                        code: None,
+                        signal: libc::WIFSIGNALED(status).then(|| libc::WTERMSIG(status)),
+                    }
+                };
+                exits.push((pid, exit));
+                false
+            }
+        }
+    });
+    exits
+}
+
+#[cfg(not(unix))]
+pub fn reap_children() -> Vec<(u32, ExitStatus)> {
+    Vec::new()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reaps_tracked_children_with_their_exit_status() {
+        let pid = shell_command("exit 3").spawn().unwrap().id();
+        track_child(pid);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let exit = loop {
+            let exits = reap_children();
+            if let Some((_, exit)) = exits.into_iter().find(|(reaped, _)| *reaped == pid) {
+                break exit;
+            }
+            assert!(Instant::now() < deadline, "child was not reaped");
+            thread::sleep(Duration::from_millis(20));
+        };
+        assert_eq!(
+            exit,
+            ExitStatus {
+                code: Some(3),
+                signal: None
+            }
+        );
+    }
+}
@@ -0,0 +1,102 @@
+/// 按依赖关系计算启动顺序，返回节点下标，依赖总是排在依赖它的节点之前。
+///
+/// 每个节点为 (名称, 依赖的名称列表)，名称可以重复（集群模式的多个实例），
+/// 依赖会指向所有同名节点；不在列表中的依赖会被忽略。存在循环依赖时返回循环路径。
+pub fn dependency_order(nodes: &[(String, Vec<String>)]) -> Result<Vec<usize>, String> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Unvisited,
+        Visiting,
+        Done,
+    }
+
+    fn visit(
+        index: usize,
+        nodes: &[(String, Vec<String>)],
+        states: &mut [State],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), String> {
+        match states[index] {
+            State::Done => return Ok(()),
+            State::Visiting => {
+                // 从第一次进入该节点的位置开始就是循环
+                let start = path.iter().position(|&i| i == index).unwrap_or(0);
+                let cycle: Vec<&str> = path[start..]
+                    .iter()
+                    .chain(std::iter::once(&index))
+                    .map(|&i| nodes[i].0.as_str())
+                    .collect();
+                return Err(t!("dependency.cycle", cycle.join(" -> ")));
+            }
+            State::Unvisited => {}
+        }
+
+        states[index] = State::Visiting;
+        path.push(index);
+        for dep in &nodes[index].1 {
+            for (i, (name, _)) in nodes.iter().enumerate() {
+                if name == dep {
+                    visit(i, nodes, states, path, order)?;
+                }
+            }
+        }
+        path.pop();
+        states[index] = State::Done;
+        order.push(index);
+        Ok(())
+    }
+
+    let mut states = vec![State::Unvisited; nodes.len()];
+    let mut order = Vec::with_capacity(nodes.len());
+    for index in 0..nodes.len() {
+        visit(index, nodes, &mut states, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nodes(items: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+        items
+            .iter()
+            .map(|(name, deps)| {
+                (
+                    name.to_string(),
+                    deps.iter().map(|d| d.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dependencies_come_first() {
+        let nodes = nodes(&[("web", &["api"]), ("api", &["db"]), ("db", &[])]);
+        assert_eq!(dependency_order(&nodes).unwrap(), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn duplicate_names_are_all_ordered() {
+        // 集群模式下 api 有两个实例，web 依赖全部实例
+        let nodes = nodes(&[("web", &["api"]), ("api", &[]), ("api", &[])]);
+        assert_eq!(dependency_order(&nodes).unwrap(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn missing_dependencies_are_ignored() {
+        let nodes = nodes(&[("web", &["cache"]), ("db", &[])]);
+        assert_eq!(dependency_order(&nodes).unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let nodes = nodes(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"])]);
+        let error = dependency_order(&nodes).unwrap_err();
+        assert!(error.contains("a -> b -> c -> a"), "{}", error);
+
+        let nodes = self::nodes(&[("a", &["a"])]);
+        assert!(dependency_order(&nodes).unwrap_err().contains("a -> a"));
+    }
+}
@@ -0,0 +1,251 @@
+use super::super::config::events::append_event;
+use super::super::config::settings::{expand_home, load_settings, EventSink, SinkTarget};
+use super::child::{shell_command, wait_timeout};
+use super::http::{parse_http_url, send_request};
+use super::process::{unix_now, PmrProcessInfo};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::Stdio;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// 单个接收方的超时时间，避免拖慢进程操作
+const SINK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EventKind {
+    Start,
+    Stop,
+    Exit,
+    Restart,
+    CrashLoop,
+    MemoryLimit,
+    HealthFail,
+}
+
+impl EventKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::Start => "start",
+            EventKind::Stop => "stop",
+            EventKind::Exit => "exit",
+            EventKind::Restart => "restart",
+            EventKind::CrashLoop => "crash-loop",
+            EventKind::MemoryLimit => "memory-limit",
+            EventKind::HealthFail => "health-fail",
+        }
+    }
+}
+
+/// 发送给接收方的事件
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Event {
+    pub event: String,
+    pub timestamp: u64, // Unix时间戳（秒）
+    pub pmr_id: u32,
+    pub name: String,
+    pub namespace: String,
+    pub pid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal: Option<i32>,
+    // 触发事件的原因，例如 "user"、"health check"，只记录不翻译的代码，显示时再翻译
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failures: Option<u32>, // crash-loop、health-fail 的失败次数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<u64>, // crash-loop 统计的时间范围（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<u64>, // memory-limit 时的内存占用（字节）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>, // memory-limit 的内存上限（字节）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>, // health-fail 时健康检查的输出
+}
+
+impl Event {
+    pub fn new(kind: EventKind, process: &PmrProcessInfo, reason: impl Into<String>) -> Self {
+        Self {
+            event: kind.name().to_string(),
+            timestamp: unix_now(),
+            pmr_id: process.pmr_id,
+            name: process.name.clone(),
+            namespace: process.namespace.clone(),
+            pid: process.pid,
+            exit_code: None,
+            signal: None,
+            reason: reason.into(),
+            failures: None,
+            window: None,
+            memory: None,
+            limit: None,
+            detail: None,
+        }
+    }
+
+    pub fn with_pid(mut self, pid: u32) -> Self {
+        self.pid = pid;
+        self
+    }
+}
+
+/// 在后台按顺序发送事件的线程
+#[derive(Default)]
+struct Delivery {
+    sender: Option<Sender<Event>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+static DELIVERY: Lazy<Mutex<Delivery>> = Lazy::new(|| Mutex::new(Delivery::default()));
+
+/// 记录事件到事件日志，并在后台线程中发送到 ~/.pmr/config 中配置的所有接收方，
+/// 失败只打印警告。接收方可能很慢，发送不会阻塞停止、重启等操作
+pub fn emit(event: Event) {
+    if let Err(e) = append_event(&event) {
+        eprintln!("{}", t!("event.log_failed", e));
+    }
+
+    let mut delivery = DELIVERY.lock().unwrap();
+    if delivery.sender.is_none() {
+        let (sender, receiver) = mpsc::channel::<Event>();
+        delivery.sender = Some(sender);
+        delivery.worker = Some(thread::spawn(move || {
+            for event in receiver {
+                deliver_event(&event);
+            }
+        }));
+    }
+    if let Some(sender) = &delivery.sender {
+        let _ = sender.send(event);
+    }
+}
+
+/// 等待已发出的事件发送完成，进程退出前调用，否则未发送的事件会丢失
+pub fn flush_events() {
+    let worker = {
+        let mut delivery = DELIVERY.lock().unwrap();
+        // 关闭通道后发送线程处理完剩余事件即退出
+        delivery.sender = None;
+        delivery.worker.take()
+    };
+    if let Some(worker) = worker {
+        let _ = worker.join();
+    }
+}
+
+fn deliver_event(event: &Event) {
+    let settings = match load_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}", t!("event.settings_failed", e));
+            return;
+        }
+    };
+
+    let json = match serde_json::to_string(event) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("{}", t!("event.serialize_failed", e));
+            return;
+        }
+    };
+    for sink in settings
+        .event_sinks
+        .iter()
+        .filter(|s| s.events.is_empty() || s.events.contains(&event.event))
+    {
+        if let Err(e) = deliver(sink, &json) {
+            eprintln!("{}", t!("event.deliver_failed", event.event, e));
+        }
+    }
+}
+
+fn deliver(sink: &EventSink, json: &str) -> io::Result<()> {
+    match &sink.target {
+        SinkTarget::Webhook { url } => {
+            let url = parse_http_url(url).map_err(io::Error::other)?;
+            let status = send_request(
+                "POST",
+                &url,
+                Some(("application/json", json.as_bytes())),
+                SINK_TIMEOUT,
+            )?;
+            if !(200..300).contains(&status) {
+                return Err(io::Error::other(t!("event.webhook_status", status)));
+            }
+            Ok(())
+        }
+        SinkTarget::Command { command } => run_command(command, json),
+        SinkTarget::File { path } => {
+            let path = expand_home(path);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", json)
+        }
+    }
+}
+
+/// 通过shell执行命令，事件JSON写入标准输入
+fn run_command(command: &str, json: &str) -> io::Result<()> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // 命令可能不读取标准输入，忽略写入错误
+        let _ = writeln!(stdin, "{}", json);
+    }
+
+    wait_timeout(&mut child, SINK_TIMEOUT)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::super::child::reap_children;
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn command_sinks_are_not_reaped_by_the_reaper() {
+        let path = std::env::temp_dir().join(format!("pmr-test-sink-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let sink = EventSink {
+            target: SinkTarget::Command {
+                command: format!("cat >> {}", path.display()),
+            },
+            events: Vec::new(),
+        };
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reaper = {
+            let done = done.clone();
+            thread::spawn(move || {
+                while !done.load(Ordering::Relaxed) {
+                    reap_children();
+                    thread::sleep(Duration::from_millis(1));
+                }
+            })
+        };
+        let results: Vec<io::Result<()>> =
+            (0..50).map(|i| deliver(&sink, &i.to_string())).collect();
+        done.store(true, Ordering::Relaxed);
+        reaper.join().unwrap();
+
+        for result in results {
+            result.unwrap();
+        }
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+        let _ = fs::remove_file(&path);
+        assert_eq!(lines, 50);
+    }
+}
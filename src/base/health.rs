@@ -0,0 +1,168 @@
+use super::child::{shell_command, wait_timeout};
+use super::http::{parse_http_url, resolve, send_request, HttpUrl};
+use super::user::{apply_credentials, Credentials};
+use serde::{Deserialize, Serialize};
+use std::net::TcpStream;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// 健康检查的默认间隔、超时（毫秒）和连续失败阈值
+pub const DEFAULT_HEALTH_INTERVAL: u64 = 10000;
+pub const DEFAULT_HEALTH_TIMEOUT: u64 = 2000;
+pub const DEFAULT_HEALTH_THRESHOLD: u32 = 3;
+
+/// 进程的健康检查配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HealthCheck {
+    pub check: String,              // http://...、tcp:<host:port> 或 cmd:<command>
+    pub expect_status: Option<u16>, // HTTP检查期望的状态码，为空时接受2xx
+    pub interval: u64,              // 检查间隔（毫秒）
+    pub timeout: u64,               // 单次检查超时（毫秒）
+    pub threshold: u32,             // 连续失败多少次后视为不健康
+    pub auto_restart: bool,         // 不健康时自动重启
+}
+
+/// 最近一次健康检查的结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HealthState {
+    pub healthy: bool,
+    pub failures: u32,   // 连续失败次数
+    pub last_check: u64, // Unix时间戳（秒）
+    pub message: String,
+}
+
+pub enum HealthProbe {
+    Http(HttpUrl),
+    Tcp(String),
+    Command(String),
+}
+
+/// 解析健康检查，格式为 `http://host[:port][/path]`、`tcp:[host:]port` 或 `cmd:<command>`
+pub fn parse_health_check(spec: &str) -> Result<HealthProbe, String> {
+    if spec.starts_with("http://") {
+        return parse_http_url(spec).map(HealthProbe::Http);
+    }
+    if let Some(addr) = spec.strip_prefix("tcp:") {
+        return match addr.parse::<u16>() {
+            Ok(port) => Ok(HealthProbe::Tcp(format!("127.0.0.1:{}", port))),
+            Err(_) if addr.contains(':') => Ok(HealthProbe::Tcp(addr.to_string())),
+            Err(_) => Err(t!("health.invalid_address", addr)),
+        };
+    }
+    if let Some(command) = spec.strip_prefix("cmd:") {
+        if !command.trim().is_empty() {
+            return Ok(HealthProbe::Command(command.to_string()));
+        }
+    }
+    Err(t!("health.invalid_spec", spec))
+}
+
+/// 作为clap的value_parser使用，校验健康检查
+pub fn validate_health_check(spec: &str) -> Result<String, String> {
+    parse_health_check(spec).map(|_| spec.to_string())
+}
+
+/// 执行一次健康检查，成功时返回结果描述，失败时返回原因。
+///
+/// 命令检查以 credentials 指定的用户和组执行，与被检查的进程相同，
+/// 被检查进程的PID通过环境变量 PMR_PID 传给命令
+pub fn run_health_check(
+    check: &HealthCheck,
+    workdir: &str,
+    pid: u32,
+    credentials: Option<&Credentials>,
+) -> Result<String, String> {
+    let timeout = Duration::from_millis(check.timeout);
+    match parse_health_check(&check.check)? {
+        HealthProbe::Http(url) => {
+            let status = send_request("GET", &url, None, timeout).map_err(|e| e.to_string())?;
+            let ok = match check.expect_status {
+                Some(expected) => status == expected,
+                None => (200..300).contains(&status),
+            };
+            if ok {
+                Ok(format!("HTTP {}", status))
+            } else {
+                Err(format!("HTTP {}", status))
+            }
+        }
+        HealthProbe::Tcp(addr) => {
+            let addr = resolve(&addr).map_err(|e| e.to_string())?;
+            TcpStream::connect_timeout(&addr, timeout)
+                .map(|_| t!("health.connected", addr))
+                .map_err(|e| format!("{}: {}", addr, e))
+        }
+        HealthProbe::Command(command) => run_command(&command, workdir, pid, timeout, credentials),
+    }
+}
+
+/// 在workdir中通过shell执行命令，退出码为0视为健康
+fn run_command(
+    command: &str,
+    workdir: &str,
+    pid: u32,
+    timeout: Duration,
+    credentials: Option<&Credentials>,
+) -> Result<String, String> {
+    let mut cmd = shell_command(command);
+    if !workdir.is_empty() {
+        cmd.current_dir(workdir);
+    }
+    cmd.env("PMR_PID", pid.to_string());
+    if let Some(credentials) = credentials {
+        apply_credentials(&mut cmd, credentials).map_err(|e| e.to_string())?;
+    }
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    wait_timeout(&mut child, timeout)
+        .map(|()| "exit 0".to_string())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes() {
+        match parse_health_check("http://localhost:3000/ping") {
+            Ok(HealthProbe::Http(url)) => {
+                assert_eq!((url.host.as_str(), url.port), ("localhost", 3000));
+                assert_eq!(url.path, "/ping");
+            }
+            _ => panic!("expected an http probe"),
+        }
+        assert!(matches!(
+            parse_health_check("tcp:6379"),
+            Ok(HealthProbe::Tcp(addr)) if addr == "127.0.0.1:6379"
+        ));
+        assert!(matches!(
+            parse_health_check("tcp:db.local:5432"),
+            Ok(HealthProbe::Tcp(addr)) if addr == "db.local:5432"
+        ));
+        assert!(matches!(
+            parse_health_check("cmd:test -f ready"),
+            Ok(HealthProbe::Command(command)) if command == "test -f ready"
+        ));
+    }
+
+    #[test]
+    fn invalid_probes() {
+        for spec in [
+            "",
+            "tcp:",
+            "tcp:redis",
+            "cmd:",
+            "cmd:  ",
+            "https://localhost/",
+            "ping",
+        ] {
+            assert!(parse_health_check(spec).is_err(), "{}", spec);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use super::super::config::log;
+use super::child::{shell_command, wait_timeout};
+use super::process::PmrProcessInfo;
+use super::user::{apply_credentials, Credentials};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::process::Stdio;
+use std::time::Duration;
+
+/// 钩子命令的默认超时时间（毫秒）
+pub const DEFAULT_HOOK_TIMEOUT: u64 = 30000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Hook {
+    pub command: String, // 通过shell执行
+    pub timeout: u64,    // 超时时间（毫秒），超时后结束命令并视为失败
+}
+
+/// 启动和停止前后执行的钩子
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Hooks {
+    pub pre_start: Option<Hook>,
+    pub post_start: Option<Hook>,
+    pub pre_stop: Option<Hook>,
+    pub post_stop: Option<Hook>,
+}
+
+#[derive(Clone, Copy)]
+pub enum HookStage {
+    PreStart,
+    PostStart,
+    PreStop,
+    PostStop,
+}
+
+impl HookStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookStage::PreStart => "pre_start",
+            HookStage::PostStart => "post_start",
+            HookStage::PreStop => "pre_stop",
+            HookStage::PostStop => "post_stop",
+        }
+    }
+
+    fn hook(self, hooks: &Hooks) -> Option<&Hook> {
+        match self {
+            HookStage::PreStart => hooks.pre_start.as_ref(),
+            HookStage::PostStart => hooks.post_start.as_ref(),
+            HookStage::PreStop => hooks.pre_stop.as_ref(),
+            HookStage::PostStop => hooks.post_stop.as_ref(),
+        }
+    }
+}
+
+/// 执行进程配置的钩子，未配置时直接返回成功。
+///
+/// 命令在进程的workdir中以进程的用户和组执行并继承进程的环境变量，输出追加到进程的日志文件。
+pub fn run_hook(
+    process: &PmrProcessInfo,
+    stage: HookStage,
+    credentials: Option<&Credentials>,
+) -> Result<(), String> {
+    let Some(hook) = stage.hook(&process.hooks) else {
+        return Ok(());
+    };
+    run_hook_command(process, stage, hook, credentials)
+        .map_err(|e| t!("hook.failed", stage.name(), e))
+}
+
+fn run_hook_command(
+    process: &PmrProcessInfo,
+    stage: HookStage,
+    hook: &Hook,
+    credentials: Option<&Credentials>,
+) -> io::Result<()> {
+    let log_path = log::get_log_path(process.pmr_id)?;
+    let mut log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+    writeln!(log_file, "[pmr] {} hook: {}", stage.name(), hook.command)?;
+
+    let mut cmd = shell_command(&hook.command);
+    if let Some(credentials) = credentials {
+        apply_credentials(&mut cmd, credentials)?;
+    }
+    cmd.envs(&process.env)
+        .env("PMR_ID", process.pmr_id.to_string())
+        .env("PMR_APP_NAME", &process.name)
+        .env("PMR_HOOK", stage.name())
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(log_file.try_clone()?))
+        .stderr(Stdio::from(log_file.try_clone()?));
+    if !process.workdir.is_empty() {
+        cmd.current_dir(&process.workdir);
+    }
+
+    let mut child = cmd.spawn()?;
+    let result = wait_timeout(&mut child, Duration::from_millis(hook.timeout)).map_err(|e| {
+        if e.kind() == io::ErrorKind::TimedOut {
+            io::Error::new(io::ErrorKind::TimedOut, t!("hook.timed_out", hook.timeout))
+        } else {
+            e
+        }
+    });
+
+    if let Err(e) = &result {
+        let _ = writeln!(log_file, "[pmr] {} hook failed: {}", stage.name(), e);
+    }
+    result
+}
@@ -0,0 +1,119 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// 解析后的 http:// 地址
+pub struct HttpUrl {
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+/// 解析 `http://host[:port][/path]`，不支持 https
+pub fn parse_http_url(url: &str) -> Result<HttpUrl, String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| t!("http.invalid_url", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse::<u16>()
+                .map_err(|_| t!("http.invalid_port", url))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(t!("http.invalid_url", url));
+    }
+    Ok(HttpUrl {
+        host: host.to_string(),
+        port,
+        path: path.to_string(),
+    })
+}
+
+pub fn resolve(addr: &str) -> io::Result<SocketAddr> {
+    addr.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, t!("http.resolve_failed", addr)))
+}
+
+/// 发送最简单的 HTTP/1.0 请求，只读取状态行并返回状态码
+pub fn send_request(
+    method: &str,
+    url: &HttpUrl,
+    body: Option<(&str, &[u8])>,
+    timeout: Duration,
+) -> io::Result<u16> {
+    let addr = resolve(&format!("{}:{}", url.host, url.port))?;
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    let mut request = format!(
+        "{} {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: pmr\r\nConnection: close\r\n",
+        method, url.path, url.host
+    );
+    if let Some((content_type, body)) = body {
+        request.push_str(&format!(
+            "Content-Type: {}\r\nContent-Length: {}\r\n",
+            content_type,
+            body.len()
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes())?;
+    if let Some((_, body)) = body {
+        stream.write_all(body)?;
+    }
+
+    let mut buf = [0u8; 256];
+    let mut head = Vec::new();
+    while !head.contains(&b'\n') {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    head.split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, t!("http.invalid_response")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls() {
+        let url = parse_http_url("http://127.0.0.1:8080/health?full=1").unwrap();
+        assert_eq!(url.host, "127.0.0.1");
+        assert_eq!(url.port, 8080);
+        assert_eq!(url.path, "/health?full=1");
+
+        let url = parse_http_url("http://localhost").unwrap();
+        assert_eq!(url.host, "localhost");
+        assert_eq!(url.port, 80);
+        assert_eq!(url.path, "/");
+    }
+
+    #[test]
+    fn invalid_urls() {
+        for url in [
+            "https://example.com",
+            "example.com",
+            "http://",
+            "http://:80/",
+            "http://host:port/",
+        ] {
+            assert!(parse_http_url(url).is_err(), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+/// 解析内存大小，例如 `512M`、`1G`、`300K` 或不带单位的字节数
+pub fn parse_memory_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| t!("memory.invalid_size", value))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => return Err(t!("memory.invalid_unit", value)),
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units() {
+        assert_eq!(parse_memory_size("100"), Ok(100));
+        assert_eq!(parse_memory_size("100B"), Ok(100));
+        assert_eq!(parse_memory_size("2k"), Ok(2048));
+        assert_eq!(parse_memory_size("512M"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_memory_size("1 GB"), Ok(1024 * 1024 * 1024));
+        assert_eq!(parse_memory_size("1.5G"), Ok(1536 * 1024 * 1024));
+    }
+
+    #[test]
+    fn invalid_sizes() {
+        assert!(parse_memory_size("").is_err());
+        assert!(parse_memory_size("M").is_err());
+        assert!(parse_memory_size("10T").unwrap_err().contains("10T"));
+        assert!(parse_memory_size("1.2.3M").is_err());
+    }
+}
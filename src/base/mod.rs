@@ -1 +1,11 @@
+pub mod child;
+pub mod dependency;
+pub mod event;
+pub mod health;
+pub mod hook;
+pub mod http;
+pub mod memory;
 pub mod process;
+pub mod ready;
+pub mod schedule;
+pub mod user;
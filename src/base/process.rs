@@ -1,4 +1,36 @@
+use super::health::{HealthCheck, HealthState};
+use super::hook::Hooks;
+use super::ready::DEFAULT_LISTEN_TIMEOUT;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// status_history 和 restart_history 最多保留的记录条数
+const MAX_STATUS_HISTORY: usize = 20;
+
+/// 文件变化后等待多久再重启（毫秒），期间的变化会合并为一次重启
+pub const DEFAULT_WATCH_DELAY: u64 = 1000;
+
+pub fn default_ignore_watch() -> Vec<String> {
+    vec![
+        "node_modules".to_string(),
+        ".git".to_string(),
+        "target".to_string(),
+    ]
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatusChange {
+    pub status: String,
+    pub timestamp: u64, // Unix时间戳（秒）
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RestartRecord {
+    pub reason: String,
+    pub timestamp: u64, // Unix时间戳（秒）
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PmrProcessInfo {
@@ -10,5 +42,259 @@ pub struct PmrProcessInfo {
     pub program: String,
     pub workdir: String,
     pub args: Vec<String>,
-    pub restarts: u32, // 重启次数
+    pub restarts: u32,   // 重启次数
+    pub start_time: u64, // 进程启动时间，用于防止PID复用
+    pub env: BTreeMap<String, String>,
+    pub config_source: Option<String>, // 配置文件路径，命令行启动时为空
+    pub status_history: Vec<StatusChange>,
+    pub last_exit_code: Option<i32>,
+    pub max_memory_restart: Option<u64>, // 内存上限（字节），超出后自动重启
+    pub restart_history: Vec<RestartRecord>,
+    pub cron_restart: Option<String>, // 定时重启的cron表达式
+    pub watch: bool,                  // 文件变化时自动重启
+    pub watch_paths: Vec<String>,     // 监听的路径，为空时监听workdir
+    pub ignore_watch: Vec<String>,    // 忽略的路径glob
+    pub watch_delay: u64,             // 防抖延迟（毫秒）
+    pub instance_id: Option<u32>,     // 集群模式下的实例编号，从0开始
+    pub wait_ready: Option<String>,   // 就绪信号，未配置时启动后立即视为就绪
+    pub listen_timeout: u64,          // 等待就绪信号的超时时间（毫秒）
+    pub health_check: Option<HealthCheck>,
+    pub health: Option<HealthState>, // 最近一次健康检查的结果，由 pmr daemon 更新
+    pub depends_on: Vec<String>,     // 依赖的应用名称，启动时先启动，停止时后停止
+    pub hooks: Hooks,
+    pub user: Option<String>,  // 运行进程的用户，为空时与pmr相同
+    pub group: Option<String>, // 运行进程的主组，为空时使用用户的主组
+    pub groups: Vec<String>,   // 附加组，为空时使用 /etc/group 中包含该用户的组
+}
+
+impl PmrProcessInfo {
+    pub fn new(
+        name: String,
+        namespace: String,
+        workdir: String,
+        program: String,
+        args: Vec<String>,
+    ) -> Self {
+        Self {
+            pmr_id: 0,
+            pid: 0,
+            name,
+            namespace,
+            status: "starting".to_string(),
+            program,
+            workdir,
+            args,
+            restarts: 0,
+            start_time: 0,
+            env: BTreeMap::new(),
+            config_source: None,
+            status_history: Vec::new(),
+            last_exit_code: None,
+            max_memory_restart: None,
+            restart_history: Vec::new(),
+            cron_restart: None,
+            watch: false,
+            watch_paths: Vec::new(),
+            ignore_watch: default_ignore_watch(),
+            watch_delay: DEFAULT_WATCH_DELAY,
+            instance_id: None,
+            wait_ready: None,
+            listen_timeout: DEFAULT_LISTEN_TIMEOUT,
+            health_check: None,
+            health: None,
+            depends_on: Vec::new(),
+            hooks: Hooks::default(),
+            user: None,
+            group: None,
+            groups: Vec::new(),
+        }
+    }
+
+    /// 记录一次状态变化，只保留最近的 MAX_STATUS_HISTORY 条
+    pub fn record_status(&mut self, status: &str) {
+        self.status = status.to_string();
+        if self.status_history.last().map(|s| s.status.as_str()) == Some(status) {
+            return;
+        }
+        self.status_history.push(StatusChange {
+            status: status.to_string(),
+            timestamp: unix_now(),
+        });
+        if self.status_history.len() > MAX_STATUS_HISTORY {
+            let excess = self.status_history.len() - MAX_STATUS_HISTORY;
+            self.status_history.drain(..excess);
+        }
+    }
+
+    /// 记录一次重启及其原因，只保留最近的 MAX_STATUS_HISTORY 条
+    pub fn record_restart(&mut self, reason: &str) {
+        self.restarts = self.restarts.saturating_add(1);
+        self.restart_history.push(RestartRecord {
+            reason: reason.to_string(),
+            timestamp: unix_now(),
+        });
+        if self.restart_history.len() > MAX_STATUS_HISTORY {
+            let excess = self.restart_history.len() - MAX_STATUS_HISTORY;
+            self.restart_history.drain(..excess);
+        }
+    }
+
+    /// 进程在运行但健康检查连续失败达到阈值
+    pub fn is_unhealthy(&self) -> bool {
+        self.health_check.is_some() && self.health.as_ref().is_some_and(|h| !h.healthy)
+    }
+
+    /// 记录的PID是否仍然指向由pmr启动的那个进程
+    pub fn is_alive(&self) -> bool {
+        is_same_process(self.pid, self.start_time)
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 读取进程的启动时间。
+///
+/// Linux下取 `/proc/<pid>/stat` 的第22个字段（开机后的时钟节拍数），
+/// 其他平台使用 sysinfo 提供的启动时间（Unix时间戳）。
+#[cfg(target_os = "linux")]
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_start_time(&stat)
+}
+
+/// 从 `/proc/<pid>/stat` 的内容中取出启动时间，僵尸进程返回 None
+#[cfg(target_os = "linux")]
+fn parse_start_time(stat: &str) -> Option<u64> {
+    // 进程名可能包含空格和括号，从最后一个')'之后开始解析
+    let rest = &stat[stat.rfind(')')? + 1..];
+    // rest 从第3个字段(state)开始，starttime 是第22个字段
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // 僵尸进程已经退出，只是还没有被父进程回收
+    if fields.first() == Some(&"Z") {
+        return None;
+    }
+    fields.get(19)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    use sysinfo::{PidExt, ProcessExt, System, SystemExt};
+
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_process(pid);
+    sys.process(pid).map(|p| p.start_time())
+}
+
+/// 旧版本dump.json中的记录没有启动时间。PID对应进程的命令行与记录的程序和参数一致时，
+/// 认为仍是pmr启动的那个进程并返回它的启动时间，否则返回0（视为已停止）
+pub fn migrate_start_time(pid: u32, program: &str, args: &[String]) -> u64 {
+    if pid == 0 {
+        return 0;
+    }
+    let Some(cmdline) = process_command_line(pid) else {
+        return 0;
+    };
+    let Some((argv0, rest)) = cmdline.split_first() else {
+        return 0;
+    };
+    // 程序可能以相对路径或PATH中的名称启动，argv[0] 保留启动时的写法
+    let file_name = |path: &str| Path::new(path).file_name().map(|n| n.to_os_string());
+    let same_program = argv0 == program || file_name(argv0) == file_name(program);
+    if !same_program || rest != args {
+        return 0;
+    }
+    process_start_time(pid).unwrap_or(0)
+}
+
+#[cfg(target_os = "linux")]
+fn process_command_line(pid: u32) -> Option<Vec<String>> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    // 每个参数以'\0'结尾，参数本身可以为空
+    let raw = raw.strip_suffix(&[0]).unwrap_or(&raw);
+    if raw.is_empty() {
+        return None;
+    }
+    Some(
+        raw.split(|b| *b == 0)
+            .map(|arg| String::from_utf8_lossy(arg).to_string())
+            .collect(),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_command_line(pid: u32) -> Option<Vec<String>> {
+    use sysinfo::{PidExt, ProcessExt, System, SystemExt};
+
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_process(pid);
+    sys.process(pid).map(|p| p.cmd().to_vec())
+}
+
+/// 检查PID对应的进程是否存在且启动时间一致。
+///
+/// 启动时间未知时视为不同进程，避免误杀复用了该PID的无关进程。
+/// 旧版本dump.json中的记录在读取时通过 [`migrate_start_time`] 补上启动时间。
+pub fn is_same_process(pid: u32, start_time: u64) -> bool {
+    if pid == 0 || start_time == 0 {
+        return false;
+    }
+    process_start_time(pid) == Some(start_time)
+}
+
+/// 显示的进程状态，只在记录与进程实际情况不符时修正，list 和 describe 共用。
+///
+/// 不存在的进程记录为意外退出时显示 exited，否则显示 stopped；仍在运行但记录为已停止的进程显示 running，
+/// 等待就绪的 starting 和健康检查失败的 unhealthy 保持不变
+pub fn display_status(status: &str, alive: bool) -> &str {
+    match (alive, status) {
+        (false, "exited") => "exited",
+        (false, _) => "stopped",
+        (true, "stopped" | "exited" | "") => "running",
+        (true, status) => status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn display_status_follows_the_process() {
+        use super::display_status;
+
+        assert_eq!(display_status("exited", false), "exited");
+        assert_eq!(display_status("running", false), "stopped");
+        assert_eq!(display_status("unhealthy", false), "stopped");
+        assert_eq!(display_status("exited", true), "running");
+        assert_eq!(display_status("starting", true), "starting");
+        assert_eq!(display_status("unhealthy", true), "unhealthy");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn start_time_from_proc_stat() {
+        use super::parse_start_time;
+
+        let stat = "1234 (sleep) S 1 1234 1234 0 -1 4194304 90 0 0 0 0 0 0 0 20 0 1 0 \
+                    987654 8474624 220 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 3 0 0";
+        assert_eq!(parse_start_time(stat), Some(987654));
+        // 进程名中的空格和括号不影响字段位置
+        let stat = "42 (my (odd) app) R 1 42 42 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 5555 0 0";
+        assert_eq!(parse_start_time(stat), Some(5555));
+        let zombie = "42 (app) Z 1 42 42 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 5555 0 0";
+        assert_eq!(parse_start_time(zombie), None);
+        assert_eq!(parse_start_time("42 (app) S 1 2"), None);
+        assert_eq!(parse_start_time("garbage"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn start_time_of_this_process() {
+        assert!(super::process_start_time(std::process::id()).is_some());
+    }
 }
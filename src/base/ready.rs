@@ -0,0 +1,279 @@
+use super::super::config::log;
+use super::process::PmrProcessInfo;
+use super::user::Credentials;
+use regex::Regex;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 等待就绪信号的默认超时时间（毫秒）
+pub const DEFAULT_LISTEN_TIMEOUT: u64 = 3000;
+
+// 检查就绪信号的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 应用就绪的信号
+pub enum ReadySignal {
+    Log(Regex),    // 标准输出中出现匹配的行
+    File(PathBuf), // 文件出现或被更新，相对路径基于workdir
+    Port(u16),     // 本机TCP端口可以连接
+    Notify,        // 通过 NOTIFY_SOCKET 发送 READY=1
+}
+
+/// 解析就绪信号，格式为 `log:<regex>`、`file:<path>`、`port:<port>` 或 `notify`
+pub fn parse_ready_signal(spec: &str) -> Result<ReadySignal, String> {
+    let (kind, value) = spec.split_once(':').unwrap_or((spec, ""));
+    match kind {
+        "log" if !value.is_empty() => Regex::new(value)
+            .map(ReadySignal::Log)
+            .map_err(|e| t!("ready.invalid_regex", value, e)),
+        "file" if !value.is_empty() => Ok(ReadySignal::File(PathBuf::from(value))),
+        "port" => value
+            .parse::<u16>()
+            .map(ReadySignal::Port)
+            .map_err(|_| t!("ready.invalid_port", value)),
+        "notify" if value.is_empty() => Ok(ReadySignal::Notify),
+        _ => Err(t!("ready.invalid_spec", spec)),
+    }
+}
+
+/// 作为clap的value_parser使用，校验就绪信号
+pub fn validate_ready_signal(spec: &str) -> Result<String, String> {
+    parse_ready_signal(spec).map(|_| spec.to_string())
+}
+
+/// 等待就绪失败的原因
+pub enum ReadyError {
+    Exited(ExitStatus),
+    Timeout,
+    Io(io::Error),
+}
+
+impl std::fmt::Display for ReadyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadyError::Exited(status) => write!(f, "{}", t!("ready.exited", status)),
+            ReadyError::Timeout => write!(f, "{}", t!("ready.timed_out")),
+            ReadyError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// 检查状态，需要在启动进程之前创建，以便只识别新进程发出的信号
+enum Probe {
+    Log {
+        regex: Regex,
+        path: PathBuf,
+        offset: u64,
+        partial: String,
+    },
+    File {
+        path: PathBuf,
+        before: Option<SystemTime>,
+    },
+    Port(SocketAddr),
+    #[cfg(unix)]
+    Notify {
+        socket: std::os::unix::net::UnixDatagram,
+        path: PathBuf,
+    },
+}
+
+pub struct ReadyWaiter {
+    probe: Probe,
+}
+
+impl ReadyWaiter {
+    /// 根据进程的 wait_ready 配置准备检查，未配置时返回 None。
+    ///
+    /// credentials 是进程将要使用的用户和组，notify 的socket需要允许该用户写入
+    pub fn prepare(
+        process: &PmrProcessInfo,
+        credentials: Option<&Credentials>,
+    ) -> io::Result<Option<Self>> {
+        let Some(spec) = process.wait_ready.as_deref() else {
+            return Ok(None);
+        };
+        let signal =
+            parse_ready_signal(spec).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let probe = match signal {
+            ReadySignal::Log(regex) => {
+                // 只检查启动之后追加到日志中的内容
+                let path = log::get_log_path(process.pmr_id)?;
+                let offset = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                Probe::Log {
+                    regex,
+                    path,
+                    offset,
+                    partial: String::new(),
+                }
+            }
+            ReadySignal::File(path) => {
+                let path = PathBuf::from(&process.workdir).join(path);
+                let before = fs::metadata(&path).and_then(|m| m.modified()).ok();
+                Probe::File { path, before }
+            }
+            ReadySignal::Port(port) => Probe::Port(SocketAddr::from(([127, 0, 0, 1], port))),
+            ReadySignal::Notify => Self::notify_probe(process, credentials)?,
+        };
+        Ok(Some(Self { probe }))
+    }
+
+    #[cfg(unix)]
+    fn notify_probe(
+        process: &PmrProcessInfo,
+        credentials: Option<&Credentials>,
+    ) -> io::Result<Probe> {
+        let path = std::env::temp_dir().join(format!(
+            "pmr-notify-{}-{}.sock",
+            std::process::id(),
+            process.pmr_id
+        ));
+        let _ = fs::remove_file(&path);
+        let socket = std::os::unix::net::UnixDatagram::bind(&path)?;
+        socket.set_nonblocking(true)?;
+        // socket由pmr创建，以其他用户运行的进程需要成为其所有者才能发送 READY=1
+        if let Some(credentials) = credentials {
+            std::os::unix::fs::chown(&path, Some(credentials.uid), Some(credentials.gid))?;
+        }
+        Ok(Probe::Notify { socket, path })
+    }
+
+    #[cfg(not(unix))]
+    fn notify_probe(
+        _process: &PmrProcessInfo,
+        _credentials: Option<&Credentials>,
+    ) -> io::Result<Probe> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            t!("ready.notify_unix_only"),
+        ))
+    }
+
+    /// 需要传给子进程的 NOTIFY_SOCKET 路径
+    pub fn notify_socket(&self) -> Option<&PathBuf> {
+        match &self.probe {
+            #[cfg(unix)]
+            Probe::Notify { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// 等待进程就绪，进程退出或超时返回错误
+    pub fn wait(&mut self, child: &mut Child, timeout: Duration) -> Result<(), ReadyError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.check().map_err(ReadyError::Io)? {
+                return Ok(());
+            }
+            if let Some(status) = child.try_wait().map_err(ReadyError::Io)? {
+                return Err(ReadyError::Exited(status));
+            }
+            if Instant::now() >= deadline {
+                return Err(ReadyError::Timeout);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn check(&mut self) -> io::Result<bool> {
+        match &mut self.probe {
+            Probe::Log {
+                regex,
+                path,
+                offset,
+                partial,
+            } => {
+                let Ok(mut file) = File::open(&*path) else {
+                    return Ok(false);
+                };
+                file.seek(SeekFrom::Start(*offset))?;
+                let mut buf = Vec::new();
+                *offset += file.read_to_end(&mut buf)? as u64;
+                partial.push_str(&String::from_utf8_lossy(&buf));
+
+                // 最后一段可能是不完整的行，留到下次再检查
+                let complete = match partial.rfind('\n') {
+                    Some(end) => partial.drain(..=end).collect::<String>(),
+                    None => return Ok(false),
+                };
+                Ok(complete.lines().any(|line| regex.is_match(line)))
+            }
+            Probe::File { path, before } => Ok(fs::metadata(&*path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| Some(modified) != *before)),
+            Probe::Port(addr) => Ok(TcpStream::connect_timeout(addr, POLL_INTERVAL).is_ok()),
+            #[cfg(unix)]
+            Probe::Notify { socket, .. } => {
+                let mut buf = [0u8; 4096];
+                loop {
+                    match socket.recv(&mut buf) {
+                        Ok(n) => {
+                            let message = String::from_utf8_lossy(&buf[..n]);
+                            if message.lines().any(|line| line.trim() == "READY=1") {
+                                return Ok(true);
+                            }
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ReadyWaiter {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Probe::Notify { path, .. } = &self.probe {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals() {
+        match parse_ready_signal("log:listening on \\d+") {
+            Ok(ReadySignal::Log(regex)) => assert!(regex.is_match("listening on 8080")),
+            _ => panic!("expected a log signal"),
+        }
+        assert!(matches!(
+            parse_ready_signal("file:run/ready"),
+            Ok(ReadySignal::File(path)) if path == std::path::Path::new("run/ready")
+        ));
+        assert!(matches!(
+            parse_ready_signal("port:8080"),
+            Ok(ReadySignal::Port(8080))
+        ));
+        assert!(matches!(
+            parse_ready_signal("notify"),
+            Ok(ReadySignal::Notify)
+        ));
+    }
+
+    #[test]
+    fn invalid_signals() {
+        for spec in [
+            "",
+            "log:",
+            "log:(",
+            "file:",
+            "port:http",
+            "port:70000",
+            "notify:x",
+            "tcp:80",
+        ] {
+            assert!(parse_ready_signal(spec).is_err(), "{}", spec);
+        }
+    }
+}
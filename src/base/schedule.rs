@@ -0,0 +1,23 @@
+use chrono::{DateTime, Local};
+use croner::Cron;
+
+/// 解析cron表达式，支持标准的5段（分 时 日 月 周）和带秒的6段格式
+pub fn parse_cron(expr: &str) -> Result<Cron, String> {
+    Cron::new(expr)
+        .with_seconds_optional()
+        .parse()
+        .map_err(|e| t!("schedule.invalid_cron", expr, e))
+}
+
+/// 计算 `after` 之后的下一次触发时间
+pub fn next_fire(expr: &str, after: &DateTime<Local>) -> Option<DateTime<Local>> {
+    parse_cron(expr)
+        .ok()?
+        .find_next_occurrence(after, false)
+        .ok()
+}
+
+/// 作为clap的value_parser使用，校验cron表达式
+pub fn validate_cron(expr: &str) -> Result<String, String> {
+    parse_cron(expr).map(|_| expr.to_string())
+}
@@ -0,0 +1,214 @@
+use super::process::PmrProcessInfo;
+use std::fs;
+use std::io;
+use std::process::Command;
+
+const PASSWD_PATH: &str = "/etc/passwd";
+const GROUP_PATH: &str = "/etc/group";
+
+/// /etc/passwd 中的一个用户
+struct PasswdEntry {
+    name: String,
+    uid: u32,
+    gid: u32,
+    home: String,
+}
+
+/// /etc/group 中的一个组
+struct GroupEntry {
+    name: String,
+    gid: u32,
+    members: Vec<String>,
+}
+
+/// 启动进程时切换到的用户、主组和附加组
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub uid: u32,
+    pub gid: u32,
+    pub groups: Vec<u32>,
+    pub user: Option<(String, String)>, // 切换用户时的用户名和主目录，用于设置 USER 和 HOME
+}
+
+fn read_passwd() -> io::Result<Vec<PasswdEntry>> {
+    Ok(fs::read_to_string(PASSWD_PATH)?
+        .lines()
+        .filter_map(|line| {
+            // name:password:uid:gid:gecos:home:shell
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 7 || line.starts_with('#') {
+                return None;
+            }
+            Some(PasswdEntry {
+                name: fields[0].to_string(),
+                uid: fields[2].parse().ok()?,
+                gid: fields[3].parse().ok()?,
+                home: fields[5].to_string(),
+            })
+        })
+        .collect())
+}
+
+fn read_groups() -> io::Result<Vec<GroupEntry>> {
+    Ok(fs::read_to_string(GROUP_PATH)?
+        .lines()
+        .filter_map(|line| {
+            // name:password:gid:member,member
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 4 || line.starts_with('#') {
+                return None;
+            }
+            Some(GroupEntry {
+                name: fields[0].to_string(),
+                gid: fields[2].parse().ok()?,
+                members: fields[3]
+                    .split(',')
+                    .filter(|m| !m.is_empty())
+                    .map(String::from)
+                    .collect(),
+            })
+        })
+        .collect())
+}
+
+/// 按名称或数字ID查找用户
+fn find_user(value: &str) -> Result<PasswdEntry, String> {
+    let users = read_passwd().map_err(|e| t!("user.read_failed", PASSWD_PATH, e))?;
+    let uid = value.parse::<u32>().ok();
+    users
+        .into_iter()
+        .find(|u| u.name == value || Some(u.uid) == uid)
+        .ok_or_else(|| t!("user.not_found", value, PASSWD_PATH))
+}
+
+/// 按名称或数字ID查找组
+fn find_group(groups: &[GroupEntry], value: &str) -> Result<u32, String> {
+    let gid = value.parse::<u32>().ok();
+    groups
+        .iter()
+        .find(|g| g.name == value || Some(g.gid) == gid)
+        .map(|g| g.gid)
+        .ok_or_else(|| t!("user.group_not_found", value, GROUP_PATH))
+}
+
+/// 作为clap的value_parser使用，校验用户存在于 /etc/passwd
+pub fn validate_user(value: &str) -> Result<String, String> {
+    find_user(value).map(|_| value.to_string())
+}
+
+/// 作为clap的value_parser使用，校验组存在于 /etc/group
+pub fn validate_group(value: &str) -> Result<String, String> {
+    let groups = read_groups().map_err(|e| t!("user.read_failed", GROUP_PATH, e))?;
+    find_group(&groups, value).map(|_| value.to_string())
+}
+
+/// 解析进程配置的用户和组，都未配置时返回 None，以pmr自身的身份运行。
+///
+/// 只指定用户时使用该用户的主组，附加组为 /etc/group 中包含该用户的组；
+/// 指定了附加组时只使用这些组。
+pub fn resolve_credentials(
+    user: Option<&str>,
+    group: Option<&str>,
+    groups: &[String],
+) -> Result<Option<Credentials>, String> {
+    if user.is_none() && group.is_none() && groups.is_empty() {
+        return Ok(None);
+    }
+
+    let entry = user.map(find_user).transpose()?;
+    let group_list = read_groups().map_err(|e| t!("user.read_failed", GROUP_PATH, e))?;
+    let (current_uid, current_gid) = current_ids();
+    let uid = entry.as_ref().map_or(current_uid, |u| u.uid);
+    let gid = match group {
+        Some(group) => find_group(&group_list, group)?,
+        None => entry.as_ref().map_or(current_gid, |u| u.gid),
+    };
+
+    let mut supplementary = vec![gid];
+    if groups.is_empty() {
+        if let Some(entry) = &entry {
+            supplementary.extend(
+                group_list
+                    .iter()
+                    .filter(|g| g.members.contains(&entry.name))
+                    .map(|g| g.gid),
+            );
+        }
+    } else {
+        for group in groups {
+            supplementary.push(find_group(&group_list, group)?);
+        }
+    }
+    supplementary.sort_unstable();
+    supplementary.dedup();
+
+    Ok(Some(Credentials {
+        uid,
+        gid,
+        groups: supplementary,
+        user: entry.map(|u| (u.name, u.home)),
+    }))
+}
+
+/// 进程配置的用户和组。进程本身、钩子和健康检查命令都以这个身份运行，
+/// 调用方解析一次后传给需要执行的各个命令
+pub fn process_credentials(process: &PmrProcessInfo) -> io::Result<Option<Credentials>> {
+    resolve_credentials(
+        process.user.as_deref(),
+        process.group.as_deref(),
+        &process.groups,
+    )
+    .map_err(io::Error::other)
+}
+
+/// 在子进程 exec 之前依次设置附加组、主组和用户，切换用户时同时设置 USER、LOGNAME 和 HOME。
+///
+/// 只有 root 可以切换到其他用户或组，其他用户只能使用自身的用户和主组，附加组保持不变。
+#[cfg(unix)]
+pub fn apply_credentials(cmd: &mut Command, credentials: &Credentials) -> io::Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    let (current_uid, current_gid) = current_ids();
+    if current_uid != 0 && (credentials.uid != current_uid || credentials.gid != current_gid) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            t!("user.requires_root"),
+        ));
+    }
+
+    if let Some((name, home)) = &credentials.user {
+        cmd.env("USER", name).env("LOGNAME", name).env("HOME", home);
+    }
+    let (uid, gid, groups) = (credentials.uid, credentials.gid, credentials.groups.clone());
+    // pre_exec 在 fork 之后执行，只能调用异步信号安全的函数
+    unsafe {
+        cmd.pre_exec(move || {
+            if current_uid == 0 && libc::setgroups(groups.len() as _, groups.as_ptr()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::setgid(gid) != 0 || libc::setuid(uid) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn apply_credentials(_cmd: &mut Command, _credentials: &Credentials) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        t!("user.unix_only"),
+    ))
+}
+
+#[cfg(unix)]
+fn current_ids() -> (u32, u32) {
+    unsafe { (libc::geteuid(), libc::getegid()) }
+}
+
+#[cfg(not(unix))]
+fn current_ids() -> (u32, u32) {
+    (0, 0)
+}
@@ -0,0 +1,36 @@
+//! `pmr` 命令行程序使用的命令入口和参数校验，不属于库的稳定接口。
+//!
+//! 嵌入其他程序时请使用 [`ProcessManager`](crate::ProcessManager)。
+
+pub use crate::base::event::flush_events;
+pub use crate::base::health::validate_health_check;
+pub use crate::base::memory::parse_memory_size;
+pub use crate::base::ready::validate_ready_signal;
+pub use crate::base::schedule::validate_cron;
+pub use crate::base::user::{validate_group, validate_user};
+pub use crate::commands::api::serve_api;
+pub use crate::commands::daemon::run_daemon;
+pub use crate::commands::delete::delete_process;
+pub use crate::commands::describe::describe_process;
+pub use crate::commands::events::show_events;
+pub use crate::commands::list::{
+    print_processes, watch_processes, ListFormat, ListOptions, SortSpec,
+};
+pub use crate::commands::metrics::{print_metrics, serve_metrics};
+pub use crate::commands::monit::monit;
+pub use crate::commands::reload::reload_process;
+pub use crate::commands::restart::restart_process;
+pub use crate::commands::scale::scale_process;
+pub use crate::commands::schedule::list_schedules;
+pub use crate::commands::start::{parse_instances, start_process};
+pub use crate::commands::stop::stop_process;
+pub use crate::commands::tail_log;
+pub use crate::commands::watch::set_watch;
+
+use crate::config::dump::DumpConfig;
+
+/// 读取或创建 ~/.pmr 下的进程列表
+pub fn config_init() -> std::io::Result<()> {
+    DumpConfig::try_get_instance()?;
+    Ok(())
+}
@@ -0,0 +1,492 @@
+use super::super::base::child;
+use super::super::base::process::PmrProcessInfo;
+use super::super::config::dump::DumpConfig;
+use super::super::config::log;
+use super::super::error::PmrError;
+use super::list::{collect_processes, sample_system};
+use super::restart::restart_existing_process;
+use super::start::{launch_instances, process_from_json, start_existing_process};
+use super::stop::stop_and_notify;
+use super::target::{resolve_target, BatchResult};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// 接口版本，不兼容的修改需要增加版本号
+pub const API_VERSION: u64 = 1;
+
+// 跟踪日志时的检查间隔
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// 回收子进程的间隔
+const REAP_INTERVAL: Duration = Duration::from_secs(1);
+
+// 会启动或停止进程的请求依次执行，回收子进程时也需要持有，
+// 避免抢先回收正在等待就绪的子进程
+static OPERATION_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn get_socket_path() -> io::Result<PathBuf> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, t!("home.not_found")))?;
+    let pmr_dir = home_dir.join(".pmr");
+    if !pmr_dir.exists() {
+        fs::create_dir_all(&pmr_dir)?;
+    }
+    Ok(pmr_dir.join("pmr.sock"))
+}
+
+/// 一行JSON请求，version 为空时视为当前版本
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    version: Option<u64>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct TargetParams {
+    target: String,
+}
+
+#[derive(Deserialize)]
+struct LogParams {
+    target: String,
+    #[serde(default = "default_log_lines")]
+    lines: usize,
+    #[serde(default)]
+    follow: bool,
+}
+
+fn default_log_lines() -> usize {
+    100
+}
+
+/// 请求失败的原因，code 供调用方区分错误类型
+struct ApiError {
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<io::Error> for ApiError {
+    fn from(e: io::Error) -> Self {
+        ApiError::new("internal_error", e.to_string())
+    }
+}
+
+impl From<PmrError> for ApiError {
+    fn from(e: PmrError) -> Self {
+        let code = match e {
+            PmrError::NotFound(_) => "not_found",
+            // start 的参数与配置文件中的应用相同，校验失败时返回配置错误
+            PmrError::Config(message) => return ApiError::new("invalid_params", message),
+            PmrError::InvalidArgument(_) => "invalid_params",
+            PmrError::StartFailed { .. } => "start_failed",
+            _ => "internal_error",
+        };
+        ApiError::new(code, e.to_string())
+    }
+}
+
+fn result_message(id: &Value, result: Value) -> Value {
+    json!({ "version": API_VERSION, "id": id, "result": result })
+}
+
+fn error_message(id: &Value, error: &ApiError) -> Value {
+    json!({
+        "version": API_VERSION,
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+fn send(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    writeln!(writer, "{}", message)?;
+    writer.flush()
+}
+
+/// 在 ~/.pmr/pmr.sock 上提供控制接口，每个连接使用一个线程
+#[cfg(unix)]
+pub fn serve_api() -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = get_socket_path()?;
+    if path.exists() {
+        // 能连接上说明已经有服务在运行，否则是上次遗留的文件
+        if UnixStream::connect(&path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                t!("api.already_running", path.display()),
+            ));
+        }
+        fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    // 接口可以启动任意程序，只允许当前用户访问
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    println!("{}", t!("api.started", path.display()));
+
+    thread::spawn(reap_children);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    let reader = match stream.try_clone() {
+                        Ok(reader) => BufReader::new(reader),
+                        Err(e) => {
+                            eprintln!("{}", t!("api.connection_failed", e));
+                            return;
+                        }
+                    };
+                    let closed = {
+                        let stream = stream.try_clone();
+                        move || stream.as_ref().map_or(true, is_closed)
+                    };
+                    handle_connection(reader, stream, &closed);
+                });
+            }
+            Err(e) => eprintln!("{}", t!("server.accept_failed", e)),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn serve_api() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        t!("api.unix_only"),
+    ))
+}
+
+/// 客户端是否已经关闭连接，只查看不读取，也不会阻塞
+#[cfg(unix)]
+fn is_closed(stream: &std::os::unix::net::UnixStream) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    let mut buf = [0u8; 1];
+    let n = unsafe {
+        libc::recv(
+            stream.as_raw_fd(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            1,
+            libc::MSG_PEEK | libc::MSG_DONTWAIT,
+        )
+    };
+    match n {
+        0 => true,
+        n if n > 0 => false,
+        _ => !matches!(
+            io::Error::last_os_error().kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+        ),
+    }
+}
+
+/// 回收由接口启动的子进程，避免产生僵尸进程
+#[cfg(unix)]
+fn reap_children() {
+    loop {
+        thread::sleep(REAP_INTERVAL);
+        let _guard = OPERATION_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        child::reap_children();
+    }
+}
+
+/// 依次处理连接上的请求，直到连接关闭。closed 检查客户端是否已经断开
+fn handle_connection(reader: impl BufRead, mut writer: impl Write, closed: &dyn Fn() -> bool) {
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = ApiError::new("parse_error", e.to_string());
+                if send(&mut writer, &error_message(&Value::Null, &error)).is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        let response = match handle_request(&request, &mut writer, closed) {
+            Ok(result) => result_message(&request.id, result),
+            Err(error) => error_message(&request.id, &error),
+        };
+        if send(&mut writer, &response).is_err() {
+            return;
+        }
+    }
+}
+
+fn params<T: for<'de> Deserialize<'de>>(request: &Request) -> Result<T, ApiError> {
+    serde_json::from_value(request.params.clone())
+        .map_err(|e| ApiError::new("invalid_params", e.to_string()))
+}
+
+fn handle_request(
+    request: &Request,
+    writer: &mut impl Write,
+    closed: &dyn Fn() -> bool,
+) -> Result<Value, ApiError> {
+    if let Some(version) = request.version {
+        if version != API_VERSION {
+            return Err(ApiError::new(
+                "unsupported_version",
+                format!(
+                    "unsupported version {}, server version is {}",
+                    version, API_VERSION
+                ),
+            ));
+        }
+    }
+
+    // 其他pmr命令可能修改了dump.json，每次请求前重新读取
+    let dump_config = DumpConfig::get_instance();
+    dump_config.reload()?;
+
+    match request.method.as_str() {
+        "version" => Ok(json!({
+            "version": API_VERSION,
+            "pmr": env!("CARGO_PKG_VERSION"),
+        })),
+        "list" => {
+            let entries = collect_processes(&sample_system(), false)?;
+            serde_json::to_value(entries).map_err(|e| io::Error::other(e).into())
+        }
+        "describe" => {
+            let TargetParams { target } = params(request)?;
+            let targets = find_targets(&target)?;
+            serde_json::to_value(targets).map_err(|e| io::Error::other(e).into())
+        }
+        "start" => start(request.params.clone()),
+        "stop" => {
+            let TargetParams { target } = params(request)?;
+            for_each_target(&target, |p| {
+                stop_and_notify(p, "user").map_err(|e| PmrError::stop_failed(p, e))
+            })
+        }
+        "restart" => {
+            let TargetParams { target } = params(request)?;
+            for_each_target(&target, |p| {
+                restart_existing_process(p, "manual")
+                    .map(|_| ())
+                    .map_err(|e| PmrError::restart_failed(p, e))
+            })
+        }
+        "delete" => {
+            let TargetParams { target } = params(request)?;
+            for_each_target(&target, |p| {
+                if p.pid > 0 {
+                    stop_and_notify(p, "deleted").map_err(|e| PmrError::delete_failed(p, e))?;
+                }
+                dump_config
+                    .delete_process(p.pmr_id)
+                    .map_err(|e| PmrError::delete_failed(p, e))
+            })
+        }
+        "log" => stream_log(request, params(request)?, writer, closed),
+        method => Err(ApiError::new(
+            "unknown_method",
+            format!("unknown method '{}'", method),
+        )),
+    }
+}
+
+fn find_targets(target: &str) -> Result<Vec<PmrProcessInfo>, ApiError> {
+    let processes = DumpConfig::get_instance().list_processes()?;
+    let targets = resolve_target(&processes, target);
+    if targets.is_empty() {
+        return Err(ApiError::new(
+            "not_found",
+            format!("no process found: {}", target),
+        ));
+    }
+    Ok(targets)
+}
+
+fn batch_result(result: &BatchResult) -> Value {
+    json!({
+        "succeeded": result.succeeded,
+        "failed": result.failed.iter().map(failure).collect::<Vec<_>>(),
+    })
+}
+
+/// 单个进程的失败原因，`{pmr_id, name, error}`
+fn failure(error: &PmrError) -> Value {
+    match error {
+        PmrError::StartFailed {
+            pmr_id,
+            name,
+            message,
+        }
+        | PmrError::StopFailed {
+            pmr_id,
+            name,
+            message,
+        }
+        | PmrError::RestartFailed {
+            pmr_id,
+            name,
+            message,
+        }
+        | PmrError::DeleteFailed {
+            pmr_id,
+            name,
+            message,
+        } => json!({ "pmr_id": pmr_id, "name": name, "error": message }),
+        error => json!({ "error": error.to_string() }),
+    }
+}
+
+/// 对目标中的每个进程执行操作，部分失败时仍然返回每个进程的结果
+fn for_each_target(
+    target: &str,
+    action: impl Fn(&PmrProcessInfo) -> Result<(), PmrError>,
+) -> Result<Value, ApiError> {
+    let targets = find_targets(target)?;
+    let _guard = OPERATION_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut result = BatchResult::default();
+    for process in &targets {
+        result.record(process, action(process));
+    }
+    Ok(batch_result(&result))
+}
+
+/// 启动已存在的进程（target），或按与配置文件中单个应用相同的字段启动新程序，
+/// 另外可以指定 namespace 和 cwd
+fn start(mut params: Value) -> Result<Value, ApiError> {
+    let Some(object) = params.as_object_mut() else {
+        return Err(ApiError::new("invalid_params", "params must be an object"));
+    };
+    let string = |value: Option<Value>| -> Result<Option<String>, ApiError> {
+        match value {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(_) => Err(ApiError::new(
+                "invalid_params",
+                "target, namespace and cwd must be strings",
+            )),
+        }
+    };
+    if let Some(target) = string(object.remove("target"))? {
+        return for_each_target(&target, |p| {
+            start_existing_process(p).map_err(|e| PmrError::start_failed(p, e))
+        });
+    }
+    if !object.contains_key("program") {
+        return Err(ApiError::new(
+            "invalid_params",
+            "either target or program is required",
+        ));
+    }
+
+    let namespace = string(object.remove("namespace"))?.unwrap_or_else(|| "default".to_string());
+    let workdir = match string(object.remove("cwd"))? {
+        Some(cwd) => cwd,
+        None => std::env::current_dir()?.to_string_lossy().to_string(),
+    };
+    let (process, instances) = process_from_json(params, namespace, workdir)?;
+
+    let _guard = OPERATION_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let pmr_ids = launch_instances(process, instances)?;
+    Ok(json!({ "pmr_id": pmr_ids.first(), "pmr_ids": pmr_ids }))
+}
+
+/// 发送日志的最后若干行，follow 为 true 时持续发送新的内容直到连接关闭。
+///
+/// 每行日志作为一条 `{"id", "log": {"pmr_id", "name", "line"}}` 消息发送，
+/// 最后返回发送的行数。
+fn stream_log(
+    request: &Request,
+    params: LogParams,
+    writer: &mut impl Write,
+    closed: &dyn Fn() -> bool,
+) -> Result<Value, ApiError> {
+    let targets = find_targets(&params.target)?;
+
+    let mut readers = Vec::new();
+    let mut sent = 0;
+    for process in &targets {
+        let path = log::get_log_path(process.pmr_id)?;
+        let Ok(mut file) = File::open(&path) else {
+            continue;
+        };
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let lines: Vec<&str> = contents.lines().collect();
+        for line in &lines[lines.len().saturating_sub(params.lines)..] {
+            send_log_line(writer, request, process, line)?;
+            sent += 1;
+        }
+        file.seek(SeekFrom::End(0))?;
+        readers.push((process, BufReader::new(file)));
+    }
+
+    if !params.follow {
+        return Ok(json!({ "lines": sent }));
+    }
+    // 日志一直没有新内容时写入不会失败，空闲时检查客户端是否已经断开
+    let mut line = String::new();
+    loop {
+        let mut has_new = false;
+        for (process, reader) in readers.iter_mut() {
+            while reader.read_line(&mut line)? > 0 {
+                // 不完整的行留到下次读取
+                if !line.ends_with('\n') {
+                    let len = line.len() as i64;
+                    reader.seek(SeekFrom::Current(-len))?;
+                    line.clear();
+                    break;
+                }
+                send_log_line(writer, request, process, line.trim_end_matches('\n'))?;
+                sent += 1;
+                line.clear();
+                has_new = true;
+            }
+        }
+        if !has_new {
+            if closed() {
+                return Ok(json!({ "lines": sent }));
+            }
+            thread::sleep(LOG_POLL_INTERVAL);
+        }
+    }
+}
+
+fn send_log_line(
+    writer: &mut impl Write,
+    request: &Request,
+    process: &PmrProcessInfo,
+    line: &str,
+) -> io::Result<()> {
+    send(
+        writer,
+        &json!({
+            "version": API_VERSION,
+            "id": request.id,
+            "log": { "pmr_id": process.pmr_id, "name": process.name, "line": line },
+        }),
+    )
+}
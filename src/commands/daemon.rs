@@ -0,0 +1,177 @@
+use super::super::base::child::{reap_children, ExitStatus};
+use super::super::base::event::{emit, Event, EventKind};
+use super::super::base::process::PmrProcessInfo;
+use super::super::base::schedule::next_fire;
+use super::super::config::dump::DumpConfig;
+use super::health::HealthChecker;
+use super::restart::{is_crashed, record_crash, restart_and_report};
+use super::watch::FileWatcher;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{PidExt, ProcessExt, System, SystemExt};
+
+// 处理文件变化事件的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 运行守护循环，定期检查由pmr管理的进程
+pub fn run_daemon(interval: Duration) -> io::Result<()> {
+    println!(
+        "{}",
+        t!("daemon.started", format!("{:.1}", interval.as_secs_f64()))
+    );
+
+    let dump_config = DumpConfig::get_instance();
+    let mut sys = System::new();
+    let mut schedules: HashMap<u32, Schedule> = HashMap::new();
+    let mut watcher = FileWatcher::new();
+    let mut health = HealthChecker::new();
+    let mut processes: Vec<PmrProcessInfo> = Vec::new();
+    let mut exits: HashMap<u32, ExitStatus> = HashMap::new();
+    let mut last_check: Option<Instant> = None;
+
+    loop {
+        if last_check.is_none_or(|t| t.elapsed() >= interval) {
+            last_check = Some(Instant::now());
+            exits.extend(reap_children());
+
+            // 其他pmr命令可能修改了dump.json，每次检查前重新读取
+            match dump_config
+                .reload()
+                .and_then(|_| dump_config.list_processes())
+            {
+                Ok(latest) => {
+                    processes = latest;
+                    sys.refresh_processes();
+                    for process in &processes {
+                        check_exit(&mut exits, process);
+                        check_memory(&sys, process);
+                        check_cron(&mut schedules, process);
+                    }
+                    // 本轮回收的子进程都已处理，其余的不是由pmr管理的进程
+                    exits.clear();
+                    schedules.retain(|id, _| processes.iter().any(|p| p.pmr_id == *id));
+                    watcher.sync(&processes);
+                }
+                Err(e) => eprintln!("{}", t!("list.read_failed", e)),
+            }
+        }
+
+        // 文件变化和健康检查按各自的间隔执行，不受检查间隔限制
+        watcher.poll();
+        health.poll(&processes);
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// 记录为运行中但已经不存在的进程视为意外退出
+fn check_exit(exits: &mut HashMap<u32, ExitStatus>, process: &PmrProcessInfo) {
+    if !is_crashed(process) {
+        return;
+    }
+
+    let exit = exits.remove(&process.pid);
+    let code = exit.and_then(|e| e.code);
+    let signal = exit.and_then(|e| e.signal);
+    let description = match (code, signal) {
+        (Some(code), _) => t!("exit.code", code),
+        (None, Some(signal)) => t!("exit.signal", signal),
+        (None, None) => t!("exit.exited"),
+    };
+    println!(
+        "{}",
+        t!("daemon.exited", process.name, process.pid, description)
+    );
+
+    if let Err(e) = record_crash(process, code, signal) {
+        eprintln!("{}", t!("daemon.update_status_failed", process.name, e));
+    }
+}
+
+fn check_memory(sys: &System, process: &PmrProcessInfo) {
+    let Some(limit) = process.max_memory_restart else {
+        return;
+    };
+    if !process.is_alive() {
+        return;
+    }
+    let Some(sys_proc) = sys.process(sysinfo::Pid::from_u32(process.pid)) else {
+        return;
+    };
+
+    let memory = sys_proc.memory();
+    if memory > limit {
+        println!(
+            "{}",
+            t!(
+                "daemon.memory_limit",
+                process.name,
+                format!("{:.1}", memory as f64 / 1024.0 / 1024.0),
+                format!("{:.1}", limit as f64 / 1024.0 / 1024.0)
+            )
+        );
+        let mut event = Event::new(EventKind::MemoryLimit, process, "");
+        event.memory = Some(memory);
+        event.limit = Some(limit);
+        emit(event);
+        restart_and_report(process, "memory limit");
+    }
+}
+
+/// 进程的下一次定时重启
+struct Schedule {
+    expr: String,
+    next: DateTime<Local>,
+}
+
+fn check_cron(schedules: &mut HashMap<u32, Schedule>, process: &PmrProcessInfo) {
+    let Some(expr) = &process.cron_restart else {
+        schedules.remove(&process.pmr_id);
+        return;
+    };
+
+    let now = Local::now();
+    // 首次检查或表达式被修改时重新计算下一次触发时间
+    let schedule = match schedules.get(&process.pmr_id) {
+        Some(schedule) if schedule.expr == *expr => schedule,
+        _ => {
+            let Some(next) = next_fire(expr, &now) else {
+                return;
+            };
+            schedules.insert(
+                process.pmr_id,
+                Schedule {
+                    expr: expr.clone(),
+                    next,
+                },
+            );
+            return;
+        }
+    };
+    if now < schedule.next {
+        return;
+    }
+
+    // 用户手动停止的进程不会被定时任务重新拉起
+    if process.status == "running" {
+        println!("{}", t!("daemon.cron_restart", process.name, expr));
+        restart_and_report(process, "cron");
+    }
+
+    match next_fire(expr, &now) {
+        Some(next) => {
+            schedules.insert(
+                process.pmr_id,
+                Schedule {
+                    expr: expr.clone(),
+                    next,
+                },
+            );
+        }
+        None => {
+            schedules.remove(&process.pmr_id);
+        }
+    }
+}
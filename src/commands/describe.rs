@@ -0,0 +1,255 @@
+use super::super::base::process::{display_status, unix_now, PmrProcessInfo};
+use super::super::config::dump::DumpConfig;
+use super::super::config::log;
+use super::super::error::PmrError;
+use super::list::{localized_table, time_to_readable};
+use sysinfo::{PidExt, ProcessExt, System, SystemExt};
+use tabled::Tabled;
+
+#[derive(Tabled)]
+struct DescribeRow {
+    field: String,
+    value: String,
+}
+
+impl DescribeRow {
+    fn new(field: &str, value: String) -> Self {
+        Self {
+            field: field.to_string(),
+            value,
+        }
+    }
+}
+
+pub fn describe_process(target: &str) -> Result<(), PmrError> {
+    let dump_config = DumpConfig::get_instance();
+    let processes = dump_config.list_processes().map_err(PmrError::Registry)?;
+
+    // 首先尝试将target解析为pmr_id，否则按name查找，集群的每个实例都会显示
+    let matched: Vec<&PmrProcessInfo> = match target
+        .parse::<u32>()
+        .ok()
+        .and_then(|pmr_id| processes.iter().find(|p| p.pmr_id == pmr_id))
+    {
+        Some(process) => vec![process],
+        None => processes.iter().filter(|p| p.name == target).collect(),
+    };
+    if matched.is_empty() {
+        return Err(PmrError::NotFound(target.to_string()));
+    }
+
+    let tables: Vec<String> = matched
+        .into_iter()
+        .map(|process| localized_table(describe_rows(process)).to_string())
+        .collect();
+    println!("{}", tables.join("\n\n"));
+    Ok(())
+}
+
+// 显示记录的状态，只在记录与进程实际情况不符时修正
+fn process_status(process: &PmrProcessInfo, alive: bool) -> String {
+    let status = if process.is_unhealthy() {
+        "unhealthy"
+    } else {
+        process.status.as_str()
+    };
+    display_status(status, alive).to_string()
+}
+
+fn describe_rows(process: &PmrProcessInfo) -> Vec<DescribeRow> {
+    // 采样两次才能得到有效的CPU使用率
+    let mut sys = System::new();
+    let pid = sysinfo::Pid::from_u32(process.pid);
+    let alive = process.is_alive();
+    if alive {
+        sys.refresh_process(pid);
+        std::thread::sleep(System::MINIMUM_CPU_UPDATE_INTERVAL);
+        sys.refresh_process(pid);
+    }
+    let sys_proc = sys.process(pid).filter(|_| alive);
+
+    let log_path = log::get_log_path(process.pmr_id)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "N/A".to_string());
+
+    let status_history = process
+        .status_history
+        .iter()
+        .map(|s| {
+            let ago = unix_now().saturating_sub(s.timestamp);
+            t!("describe.ago", s.status, time_to_readable(ago))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let restart_history = process
+        .restart_history
+        .iter()
+        .map(|r| {
+            let ago = unix_now().saturating_sub(r.timestamp);
+            t!("describe.ago", r.reason, time_to_readable(ago))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let hook_list = &process.hooks;
+    let hooks = [
+        ("pre_start", &hook_list.pre_start),
+        ("post_start", &hook_list.post_start),
+        ("pre_stop", &hook_list.pre_stop),
+        ("post_stop", &hook_list.post_stop),
+    ]
+    .iter()
+    .filter_map(|(stage, hook)| {
+        hook.as_ref()
+            .map(|h| t!("describe.hook", stage, h.command, h.timeout))
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+    vec![
+        DescribeRow::new("pmr_id", process.pmr_id.to_string()),
+        DescribeRow::new(&t!("describe.name"), process.name.clone()),
+        DescribeRow::new(&t!("describe.namespace"), process.namespace.clone()),
+        DescribeRow::new(&t!("describe.status"), process_status(process, alive)),
+        DescribeRow::new("pid", process.pid.to_string()),
+        DescribeRow::new(&t!("describe.program"), process.program.clone()),
+        DescribeRow::new(&t!("describe.args"), process.args.join(" ")),
+        DescribeRow::new(&t!("describe.workdir"), process.workdir.clone()),
+        DescribeRow::new(
+            &t!("describe.user"),
+            if process.user.is_none() && process.group.is_none() && process.groups.is_empty() {
+                "N/A".to_string()
+            } else {
+                t!(
+                    "describe.user_value",
+                    process.user.as_deref().unwrap_or("N/A"),
+                    process.group.as_deref().unwrap_or("N/A"),
+                    if process.groups.is_empty() {
+                        "N/A".to_string()
+                    } else {
+                        process.groups.join(", ")
+                    }
+                )
+            },
+        ),
+        DescribeRow::new(
+            &t!("describe.env_keys"),
+            process.env.keys().cloned().collect::<Vec<_>>().join(", "),
+        ),
+        DescribeRow::new(&t!("describe.log_path"), log_path),
+        DescribeRow::new(&t!("describe.restarts"), process.restarts.to_string()),
+        DescribeRow::new(&t!("describe.restart_history"), restart_history),
+        DescribeRow::new(
+            &t!("describe.watch"),
+            if !process.watch {
+                t!("describe.disabled")
+            } else if process.watch_paths.is_empty() {
+                t!(
+                    "describe.watch_paths",
+                    process.workdir,
+                    process.ignore_watch.join(", ")
+                )
+            } else {
+                t!(
+                    "describe.watch_paths",
+                    process.watch_paths.join(", "),
+                    process.ignore_watch.join(", ")
+                )
+            },
+        ),
+        DescribeRow::new(
+            &t!("describe.cron_restart"),
+            process
+                .cron_restart
+                .clone()
+                .unwrap_or_else(|| "N/A".to_string()),
+        ),
+        DescribeRow::new(
+            &t!("describe.max_memory_restart"),
+            process.max_memory_restart.map_or("N/A".to_string(), |m| {
+                format!("{:.1} MB", m as f64 / 1024.0 / 1024.0)
+            }),
+        ),
+        DescribeRow::new(
+            &t!("describe.depends_on"),
+            if process.depends_on.is_empty() {
+                "N/A".to_string()
+            } else {
+                process.depends_on.join(", ")
+            },
+        ),
+        DescribeRow::new(&t!("describe.hooks"), hooks),
+        DescribeRow::new(
+            &t!("describe.wait_ready"),
+            process
+                .wait_ready
+                .as_ref()
+                .map_or("N/A".to_string(), |signal| {
+                    t!("describe.wait_ready_value", signal, process.listen_timeout)
+                }),
+        ),
+        DescribeRow::new(
+            &t!("describe.health_check"),
+            process
+                .health_check
+                .as_ref()
+                .map_or("N/A".to_string(), |h| {
+                    t!(
+                        "describe.health_check_value",
+                        h.check,
+                        h.expect_status
+                            .map_or(String::new(), |s| t!("describe.expect", s)),
+                        h.interval,
+                        h.timeout,
+                        h.threshold,
+                        if h.auto_restart {
+                            t!("describe.auto_restart")
+                        } else {
+                            String::new()
+                        }
+                    )
+                }),
+        ),
+        DescribeRow::new(
+            &t!("describe.health"),
+            process.health.as_ref().map_or("N/A".to_string(), |h| {
+                t!(
+                    "describe.health_value",
+                    if h.healthy { "healthy" } else { "unhealthy" },
+                    h.failures,
+                    h.message,
+                    time_to_readable(unix_now().saturating_sub(h.last_check))
+                )
+            }),
+        ),
+        DescribeRow::new(
+            &t!("describe.uptime"),
+            sys_proc.map_or("0s".to_string(), |p| time_to_readable(p.run_time())),
+        ),
+        DescribeRow::new(
+            &t!("describe.cpu"),
+            sys_proc.map_or("0%".to_string(), |p| format!("{:.1}%", p.cpu_usage())),
+        ),
+        DescribeRow::new(
+            &t!("describe.mem"),
+            sys_proc.map_or("0 MB".to_string(), |p| {
+                format!("{:.1} MB", p.memory() as f64 / 1024.0 / 1024.0)
+            }),
+        ),
+        DescribeRow::new(
+            &t!("describe.last_exit_code"),
+            process
+                .last_exit_code
+                .map_or("N/A".to_string(), |c| c.to_string()),
+        ),
+        DescribeRow::new(&t!("describe.status_history"), status_history),
+        DescribeRow::new(
+            &t!("describe.config_source"),
+            process
+                .config_source
+                .clone()
+                .unwrap_or_else(|| t!("describe.command_line")),
+        ),
+    ]
+}
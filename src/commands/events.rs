@@ -0,0 +1,182 @@
+use super::super::base::event::Event;
+use super::super::config::events::{get_event_log_path, parse_event, read_events};
+use chrono::{Local, TimeZone};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::thread;
+use std::time::Duration;
+
+// 跟踪事件日志时的检查间隔
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 显示事件日志中的进程生命周期事件，follow 为 true 时持续输出新事件
+pub fn show_events(target: Option<&str>, lines: usize, follow: bool, json: bool) -> io::Result<()> {
+    let events: Vec<Event> = read_events()?
+        .into_iter()
+        .filter(|e| matches_target(e, target))
+        .collect();
+    if events.is_empty() && !follow && !json {
+        println!("{}", t!("events.empty"));
+        return Ok(());
+    }
+    for event in &events[events.len().saturating_sub(lines)..] {
+        print_event(event, json);
+    }
+
+    if follow {
+        follow_events(target, json)?;
+    }
+    Ok(())
+}
+
+/// 目标可以是 pmr_id、名称、命名空间或 all，进程删除后仍然可以按名称查看
+fn matches_target(event: &Event, target: Option<&str>) -> bool {
+    match target {
+        None | Some("all") => true,
+        Some(target) => {
+            event.pmr_id.to_string() == target || event.name == target || event.namespace == target
+        }
+    }
+}
+
+fn print_event(event: &Event, json: bool) {
+    if json {
+        if let Ok(line) = serde_json::to_string(event) {
+            println!("{}", line);
+        }
+        return;
+    }
+
+    let time = Local
+        .timestamp_opt(event.timestamp as i64, 0)
+        .single()
+        .map_or("N/A".to_string(), |t| {
+            t.format("%Y-%m-%d %H:%M:%S").to_string()
+        });
+    println!(
+        "{}  {}[{}]  {:<12}  {}",
+        time,
+        event.name,
+        event.pmr_id,
+        event.event,
+        describe_event(event)
+    );
+}
+
+/// 事件的可读描述
+fn describe_event(event: &Event) -> String {
+    let pid = if event.pid > 0 {
+        format!("pid {}", event.pid)
+    } else {
+        t!("events.no_pid")
+    };
+    match event.event.as_str() {
+        "start" => t!("events.spawned", pid),
+        "exit" => format!("{} {}", pid, exit_description(event)),
+        "restart" => t!("events.restarted", pid, event.reason),
+        "stop" => format!("{}: {}", pid, reason_description(&event.reason)),
+        "crash-loop" => {
+            let last = if event.reason == "exit" {
+                exit_description(event)
+            } else {
+                reason_description(&event.reason)
+            };
+            let failures = event.failures.unwrap_or_default();
+            let window = event.window.unwrap_or_default();
+            format!(
+                "{}: {}",
+                pid,
+                t!("events.crash_loop", failures, window, last)
+            )
+        }
+        "memory-limit" => {
+            let mb = |bytes: Option<u64>| {
+                format!("{:.1}", bytes.unwrap_or_default() as f64 / 1024.0 / 1024.0)
+            };
+            format!(
+                "{}: {}",
+                pid,
+                t!("events.memory_limit", mb(event.memory), mb(event.limit))
+            )
+        }
+        "health-fail" => {
+            let failures = event.failures.unwrap_or_default();
+            let detail = event.detail.as_deref().unwrap_or_default();
+            format!(
+                "{}: {}",
+                pid,
+                t!("events.health_failures", failures, detail)
+            )
+        }
+        _ => format!("{}: {}", pid, reason_description(&event.reason)),
+    }
+}
+
+fn exit_description(event: &Event) -> String {
+    match (event.exit_code, event.signal) {
+        (Some(code), _) => t!("exit.code", code),
+        (None, Some(signal)) => t!("exit.signal", signal),
+        (None, None) => t!("exit.exited"),
+    }
+}
+
+/// 停止原因的可读描述，重启原因和 describe 中一样直接显示
+fn reason_description(reason: &str) -> String {
+    match reason {
+        "user" => t!("events.stopped_by_user"),
+        "deleted" => t!("events.deleted"),
+        "scaled down" => t!("events.scaled_down"),
+        _ => reason.to_string(),
+    }
+}
+
+/// 持续读取追加到事件日志的新事件。
+///
+/// 日志被裁剪后文件会变短，此时从新文件中找到最后输出的一行，继续输出其后的内容。
+fn follow_events(target: Option<&str>, json: bool) -> io::Result<()> {
+    let path = get_event_log_path()?;
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+    let mut offset = contents.len() as u64;
+    let mut last_line = contents.lines().last().unwrap_or_default().to_string();
+    let mut partial = String::new();
+
+    loop {
+        thread::sleep(FOLLOW_INTERVAL);
+        let Ok(mut file) = File::open(&path) else {
+            continue;
+        };
+        let len = file.metadata()?.len();
+        if len == offset {
+            continue;
+        }
+
+        let mut buf = Vec::new();
+        if len < offset {
+            file.read_to_end(&mut buf)?;
+            let contents = String::from_utf8_lossy(&buf).into_owned();
+            // 找不到上次输出的行时从头输出裁剪后的内容
+            let start = match contents.rfind(&format!("{}\n", last_line)) {
+                Some(pos) if !last_line.is_empty() => pos + last_line.len() + 1,
+                _ => 0,
+            };
+            partial = contents[start..].to_string();
+        } else {
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_to_end(&mut buf)?;
+            partial.push_str(&String::from_utf8_lossy(&buf));
+        }
+        offset = len;
+
+        // 最后一段可能是不完整的行，留到下次再处理
+        let Some(end) = partial.rfind('\n') else {
+            continue;
+        };
+        let complete: String = partial.drain(..=end).collect();
+        for line in complete.lines() {
+            last_line = line.to_string();
+            if let Some(event) = parse_event(line).filter(|e| matches_target(e, target)) {
+                print_event(&event, json);
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::super::base::event::{emit, Event, EventKind};
+use super::super::base::health::{run_health_check, HealthState};
+use super::super::base::process::{unix_now, PmrProcessInfo};
+use super::super::base::user::{process_credentials, Credentials};
+use super::super::config::dump::DumpConfig;
+use super::restart::restart_and_report;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// 单个进程的检查状态
+struct AppHealth {
+    pid: u32, // 进程重启后PID变化，需要重新计数
+    next: Instant,
+    failures: u32,
+    healthy: bool,
+    credentials: Result<Option<Credentials>, String>, // 命令检查使用的用户和组，每个进程只解析一次
+}
+
+impl AppHealth {
+    fn new(process: &PmrProcessInfo, next: Instant) -> Self {
+        Self {
+            pid: process.pid,
+            next,
+            failures: 0,
+            healthy: true,
+            credentials: process_credentials(process).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// 按各进程配置的间隔执行健康检查，连续失败达到阈值时标记为 unhealthy
+#[derive(Default)]
+pub struct HealthChecker {
+    apps: HashMap<u32, AppHealth>,
+}
+
+impl HealthChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 执行到期的健康检查
+    pub fn poll(&mut self, processes: &[PmrProcessInfo]) {
+        self.apps.retain(|id, _| {
+            processes
+                .iter()
+                .any(|p| p.pmr_id == *id && p.health_check.is_some())
+        });
+
+        let now = Instant::now();
+        for process in processes {
+            let Some(check) = &process.health_check else {
+                continue;
+            };
+            // 用户手动停止的进程不做检查
+            if process.status != "running" {
+                continue;
+            }
+
+            let app = self
+                .apps
+                .entry(process.pmr_id)
+                .or_insert_with(|| AppHealth::new(process, now));
+            if app.pid != process.pid {
+                *app = AppHealth::new(process, now);
+            }
+            if now < app.next {
+                continue;
+            }
+            app.next = now + Duration::from_millis(check.interval);
+            if !process.is_alive() {
+                continue;
+            }
+
+            let result = match &app.credentials {
+                Ok(credentials) => {
+                    run_health_check(check, &process.workdir, process.pid, credentials.as_ref())
+                }
+                Err(e) => Err(e.clone()),
+            };
+            let message = match result {
+                Ok(message) => {
+                    app.failures = 0;
+                    message
+                }
+                Err(message) => {
+                    app.failures += 1;
+                    message
+                }
+            };
+            let healthy = app.failures < check.threshold;
+            if healthy != app.healthy {
+                if healthy {
+                    println!("{}", t!("health.recovered", process.name, message));
+                } else {
+                    println!(
+                        "{}",
+                        t!("health.failed", process.name, app.failures, message)
+                    );
+                    let mut event = Event::new(EventKind::HealthFail, process, "");
+                    event.failures = Some(app.failures);
+                    event.detail = Some(message.clone());
+                    emit(event);
+                }
+            }
+            app.healthy = healthy;
+
+            let state = HealthState {
+                healthy,
+                failures: app.failures,
+                last_check: unix_now(),
+                message,
+            };
+            if let Err(e) =
+                DumpConfig::get_instance().update_health(process.pmr_id, process.pid, state)
+            {
+                eprintln!("{}", t!("health.update_failed", process.name, e));
+            }
+
+            if !healthy && check.auto_restart {
+                println!("{}", t!("health.restarting", process.name));
+                restart_and_report(process, "health check");
+                // 新进程的PID在下次读取进程列表后才能得到，届时重新开始计数
+                self.apps.remove(&process.pmr_id);
+            }
+        }
+    }
+}
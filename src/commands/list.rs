@@ -1,6 +1,20 @@
+use crate::base::process::{display_status, is_same_process};
 use crate::config::dump::DumpConfig;
+use crate::error::PmrError;
+use clap::ValueEnum;
+use crossterm::style::{Print, Stylize};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use glob::Pattern;
 use serde::{Deserialize, Serialize};
-use sysinfo::{ProcessExt, System, SystemExt, UserExt};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use sysinfo::{PidExt, ProcessExt, System, SystemExt, UserExt};
+use tabled::settings::object::Rows;
+use tabled::settings::{Format, Modify};
 use tabled::{Table, Tabled};
 
 #[derive(Serialize, Deserialize)]
@@ -13,6 +27,119 @@ pub struct PmrProcess {
     pub args: Vec<String>,
     pub status: String,
     pub restarts: u32,
+    pub start_time: u64,
+    pub instance_id: Option<u32>,
+}
+
+/// 实时运行指标
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ProcessMonit {
+    pub cpu: f32,             // CPU使用率（百分比）
+    pub memory: u64,          // 常驻内存（字节）
+    pub uptime: u64,          // 运行时间（秒）
+    pub user: Option<String>, // 运行进程的用户
+}
+
+/// `pmr list` 机器可读输出中的一条记录，字段说明见 README
+#[derive(Serialize, Deserialize)]
+pub struct ProcessEntry {
+    #[serde(flatten)]
+    pub process: PmrProcess,
+    pub monit: ProcessMonit,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListFormat {
+    Table,
+    Json,
+    Yaml,
+    Csv,
+    #[value(name = "ids-only", alias = "ids")]
+    IdsOnly,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortField {
+    Id,
+    Name,
+    Cpu,
+    Mem,
+    Uptime,
+    Restarts,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SortSpec {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl FromStr for SortSpec {
+    type Err = String;
+
+    /// 解析 `<field>[:asc|desc]`，默认升序
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, order) = s.split_once(':').unwrap_or((s, "asc"));
+        let field = SortField::from_str(field, true)?;
+        let descending = match order.to_ascii_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            other => return Err(t!("list.invalid_sort", other)),
+        };
+        Ok(Self { field, descending })
+    }
+}
+
+/// list 命令的过滤和排序条件
+#[derive(Default)]
+pub struct ListOptions {
+    pub namespace: Option<String>,
+    pub status: Option<String>,
+    pub name: Option<Pattern>,
+    pub sort: Option<SortSpec>,
+}
+
+impl ListOptions {
+    fn apply(&self, entries: &mut Vec<ProcessEntry>) {
+        entries.retain(|e| {
+            let p = &e.process;
+            self.namespace.as_ref().is_none_or(|ns| p.namespace == *ns)
+                && self
+                    .status
+                    .as_ref()
+                    .is_none_or(|st| p.status.eq_ignore_ascii_case(st))
+                && self.name.as_ref().is_none_or(|pat| pat.matches(&p.name))
+        });
+
+        if let Some(sort) = self.sort {
+            entries.sort_by(|a, b| {
+                let ordering = match sort.field {
+                    SortField::Id => {
+                        (a.process.pmr_id, a.process.pid).cmp(&(b.process.pmr_id, b.process.pid))
+                    }
+                    SortField::Name => a.process.name.cmp(&b.process.name),
+                    SortField::Cpu => a.monit.cpu.total_cmp(&b.monit.cpu),
+                    SortField::Mem => a.monit.memory.cmp(&b.monit.memory),
+                    SortField::Uptime => a.monit.uptime.cmp(&b.monit.uptime),
+                    SortField::Restarts => a.process.restarts.cmp(&b.process.restarts),
+                };
+                if sort.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+    }
+}
+
+/// 用当前语言的列名替换表头，列名对应目录中的 `column.<字段>`
+pub(crate) fn localized_table<T: Tabled>(rows: Vec<T>) -> Table {
+    let mut table = Table::new(rows);
+    table.with(
+        Modify::new(Rows::first()).with(Format::content(|name| t!(&format!("column.{}", name)))),
+    );
+    table
 }
 
 #[derive(Tabled)]
@@ -21,6 +148,7 @@ struct ProcessInfo {
     name: String,
     namespace: String,
     version: String,
+    mode: String,
     pid: String,
     uptime: String,
     restarts: String,
@@ -31,7 +159,7 @@ struct ProcessInfo {
 }
 
 // 将秒数转换为可读的时间格式
-fn time_to_readable(seconds: u64) -> String {
+pub(crate) fn time_to_readable(seconds: u64) -> String {
     if seconds == 0 {
         return "0s".to_string();
     }
@@ -59,119 +187,334 @@ fn time_to_readable(seconds: u64) -> String {
     result.trim().to_string()
 }
 
-pub fn read_pmr_processes() -> Vec<PmrProcess> {
+pub fn read_pmr_processes() -> Result<Vec<PmrProcess>, PmrError> {
     let dump_config = DumpConfig::get_instance();
-    match dump_config.list_processes() {
-        Ok(processes) => processes
-            .into_iter()
-            .map(|p| PmrProcess {
-                pmr_id: p.pmr_id,
-                pid: p.pid,
-                name: p.name,
-                namespace: p.namespace,
-                program: p.program,
-                args: p.args,
-                status: p.status,
-                restarts: p.restarts,
-            })
-            .collect(),
-        Err(e) => {
-            eprintln!("Failed to read processes: {}", e);
-            Vec::new()
-        }
-    }
+    let processes = dump_config.list_processes().map_err(PmrError::Registry)?;
+    Ok(processes
+        .into_iter()
+        .map(|p| PmrProcess {
+            status: if p.is_unhealthy() {
+                "unhealthy".to_string()
+            } else {
+                p.status
+            },
+            pmr_id: p.pmr_id,
+            pid: p.pid,
+            name: p.name,
+            namespace: p.namespace,
+            program: p.program,
+            args: p.args,
+            restarts: p.restarts,
+            start_time: p.start_time,
+            instance_id: p.instance_id,
+        })
+        .collect())
 }
 
-pub fn list_processes(system: bool) {
-    let mut sys = System::new();
-    sys.refresh_all();
+/// 进程的有效用户，setuid 程序或切换了用户的进程显示实际生效的用户
+fn user_name(sys: &System, process: &sysinfo::Process) -> Option<String> {
+    process
+        .effective_user_id()
+        .or(process.user_id())
+        .and_then(|uid| sys.get_user_by_id(uid))
+        .map(|u| u.name().to_string())
+}
 
+/// 收集进程及其实时指标。system为true时返回系统中的所有进程
+pub fn collect_processes(sys: &System, system: bool) -> Result<Vec<ProcessEntry>, PmrError> {
     if system {
-        let processes: Vec<ProcessInfo> = sys
+        return Ok(sys
             .processes()
             .iter()
-            .map(|(&pid, process)| ProcessInfo {
-                id: "0".to_string(),
-                name: process.name().to_string(),
-                namespace: "default".to_string(),
-                version: "N/A".to_string(),
-                pid: pid.to_string(),
-                uptime: time_to_readable(process.run_time()),
-                restarts: "0".to_string(),
-                status: process.status().to_string(),
-                cpu: format!("{:.1}%", process.cpu_usage()),
-                mem: format!("{:.1} MB", process.memory() as f64 / 1024.0 / 1024.0),
-                user: process
-                    .user_id()
-                    .and_then(|uid| sys.get_user_by_id(uid))
-                    .map_or("N/A".to_string(), |u| u.name().to_string()),
+            .map(|(&pid, process)| ProcessEntry {
+                process: PmrProcess {
+                    pmr_id: 0,
+                    pid: pid.as_u32(),
+                    name: process.name().to_string(),
+                    namespace: "default".to_string(),
+                    program: process.exe().to_string_lossy().to_string(),
+                    args: process.cmd().iter().skip(1).cloned().collect(),
+                    status: process.status().to_string(),
+                    restarts: 0,
+                    start_time: process.start_time(),
+                    instance_id: None,
+                },
+                monit: ProcessMonit {
+                    cpu: process.cpu_usage(),
+                    memory: process.memory(),
+                    uptime: process.run_time(),
+                    user: user_name(sys, process),
+                },
             })
-            .collect();
+            .collect());
+    }
+
+    Ok(read_pmr_processes()?
+        .into_iter()
+        .map(|mut p| {
+            // 检查进程是否真的在运行，并确认PID没有被其他进程复用
+            let sys_proc = if p.pid > 0 {
+                sys.process(sysinfo::Pid::from(p.pid as usize))
+                    .filter(|_| is_same_process(p.pid, p.start_time))
+            } else {
+                None
+            };
+
+            p.status = display_status(&p.status, sys_proc.is_some()).to_string();
+            match sys_proc {
+                Some(sys_proc) => ProcessEntry {
+                    process: p,
+                    monit: ProcessMonit {
+                        cpu: sys_proc.cpu_usage(),
+                        memory: sys_proc.memory(),
+                        uptime: sys_proc.run_time(),
+                        user: user_name(sys, sys_proc),
+                    },
+                },
+                None => ProcessEntry {
+                    process: p,
+                    monit: ProcessMonit::default(),
+                },
+            }
+        })
+        .collect())
+}
+
+fn to_table_row(entry: &ProcessEntry) -> ProcessInfo {
+    let p = &entry.process;
+    let running = !matches!(p.status.as_str(), "stopped" | "exited");
+    ProcessInfo {
+        id: p.pmr_id.to_string(),
+        name: p.name.clone(),
+        namespace: p.namespace.clone(),
+        version: "N/A".to_string(),
+        mode: match p.instance_id {
+            Some(instance_id) => t!("list.mode_cluster", instance_id),
+            None => t!("list.mode_fork"),
+        },
+        pid: if running {
+            p.pid.to_string()
+        } else {
+            "0".to_string()
+        },
+        uptime: time_to_readable(entry.monit.uptime),
+        restarts: p.restarts.to_string(),
+        status: p.status.clone(),
+        cpu: if running {
+            format!("{:.1}%", entry.monit.cpu)
+        } else {
+            "0%".to_string()
+        },
+        mem: if running {
+            format!("{:.1} MB", entry.monit.memory as f64 / 1024.0 / 1024.0)
+        } else {
+            "0 MB".to_string()
+        },
+        user: entry
+            .monit
+            .user
+            .clone()
+            .unwrap_or_else(|| "N/A".to_string()),
+    }
+}
+
+// CSV字段转义：包含分隔符、引号或换行时用双引号包裹
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv(entries: &[ProcessEntry]) -> String {
+    let mut out = String::from(
+        "pmr_id,pid,name,namespace,program,args,status,restarts,start_time,instance_id,cpu,memory,uptime,user\n",
+    );
+    for e in entries {
+        let p = &e.process;
+        let fields = [
+            p.pmr_id.to_string(),
+            p.pid.to_string(),
+            p.name.clone(),
+            p.namespace.clone(),
+            p.program.clone(),
+            p.args.join(" "),
+            p.status.clone(),
+            p.restarts.to_string(),
+            p.start_time.to_string(),
+            p.instance_id.map(|i| i.to_string()).unwrap_or_default(),
+            format!("{:.1}", e.monit.cpu),
+            e.monit.memory.to_string(),
+            e.monit.uptime.to_string(),
+            e.monit.user.clone().unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn list_processes(system: bool) -> Result<(), PmrError> {
+    print_processes(system, ListFormat::Table, &ListOptions::default())
+}
+
+/// 创建并采样系统信息。CPU使用率需要两次采样之间的差值，只刷新一次时始终为0
+pub(crate) fn sample_system() -> System {
+    let mut sys = System::new();
+    sys.refresh_all();
+    // refresh_all 不会刷新用户列表，没有它用户列只能显示 N/A
+    sys.refresh_users_list();
+    thread::sleep(System::MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_processes();
+    sys
+}
 
-        let table = Table::new(processes).to_string();
-        println!("{}", table);
+pub fn print_processes(
+    system: bool,
+    format: ListFormat,
+    options: &ListOptions,
+) -> Result<(), PmrError> {
+    let sys = sample_system();
+
+    let mut entries = collect_processes(&sys, system)?;
+    options.apply(&mut entries);
+
+    match format {
+        ListFormat::Table => {
+            let rows: Vec<ProcessInfo> = entries.iter().map(to_table_row).collect();
+            println!("{}", localized_table(rows));
+        }
+        ListFormat::Json => {
+            let json = serde_json::to_string_pretty(&entries).map_err(io::Error::other)?;
+            println!("{}", json);
+        }
+        ListFormat::Yaml => {
+            let yaml = serde_yaml::to_string(&entries).map_err(io::Error::other)?;
+            print!("{}", yaml);
+        }
+        ListFormat::Csv => print!("{}", to_csv(&entries)),
+        ListFormat::IdsOnly => {
+            // --system 模式下没有pmr_id，输出PID
+            for e in &entries {
+                if system {
+                    println!("{}", e.process.pid);
+                } else {
+                    println!("{}", e.process.pmr_id);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// 用于比较两次刷新之间变化的键：--system 模式下使用PID，否则使用pmr_id
+fn entry_key(entry: &ProcessEntry, system: bool) -> u32 {
+    if system {
+        entry.process.pid
     } else {
-        let pmr_processes = read_pmr_processes();
-        let processes: Vec<ProcessInfo> = pmr_processes
+        entry.process.pmr_id
+    }
+}
+
+/// 持续刷新并原地重绘进程列表，高亮自上次刷新以来状态变化和重启次数增加的进程
+pub fn watch_processes(
+    system: bool,
+    options: &ListOptions,
+    interval: Duration,
+) -> Result<(), PmrError> {
+    ctrlc::set_handler(move || {
+        let _ = execute!(io::stdout(), cursor::Show);
+        println!();
+        std::process::exit(0);
+    })
+    .map_err(|e| io::Error::other(t!("ctrlc.failed", e)))?;
+
+    let mut stdout = io::stdout();
+    let mut sys = sample_system();
+    let mut previous: HashMap<u32, (String, u32)> = HashMap::new();
+    execute!(stdout, cursor::Hide)?;
+
+    loop {
+        let mut entries = collect_processes(&sys, system)?;
+        options.apply(&mut entries);
+
+        let rows: Vec<ProcessInfo> = entries
             .iter()
-            .map(|p| {
-                let status = if p.pid > 0 {
-                    // 检查进程是否真的在运行
-                    if let Some(sys_proc) = sys.process(sysinfo::Pid::from(p.pid as usize)) {
-                        let run_time = sys_proc.run_time();
-                        ProcessInfo {
-                            id: p.pmr_id.to_string(),
-                            name: p.name.clone(),
-                            namespace: p.namespace.clone(),
-                            version: "N/A".to_string(),
-                            pid: p.pid.to_string(),
-                            uptime: time_to_readable(run_time),
-                            restarts: p.restarts.to_string(),
-                            status: "running".to_string(),
-                            cpu: format!("{:.1}%", sys_proc.cpu_usage()),
-                            mem: format!("{:.1} MB", sys_proc.memory() as f64 / 1024.0 / 1024.0),
-                            user: sys_proc
-                                .user_id()
-                                .and_then(|uid| sys.get_user_by_id(uid))
-                                .map_or("N/A".to_string(), |u| u.name().to_string()),
-                        }
-                    } else {
-                        // 进程不存在，但PID > 0，说明进程已经退出
-                        ProcessInfo {
-                            id: p.pmr_id.to_string(),
-                            name: p.name.clone(),
-                            namespace: p.namespace.clone(),
-                            version: "N/A".to_string(),
-                            pid: p.pid.to_string(),
-                            uptime: "0s".to_string(),
-                            restarts: p.restarts.to_string(),
-                            status: "stopped".to_string(),
-                            cpu: "0%".to_string(),
-                            mem: "0 MB".to_string(),
-                            user: "N/A".to_string(),
-                        }
+            .map(|e| {
+                let mut row = to_table_row(e);
+                if let Some((status, restarts)) = previous.get(&entry_key(e, system)) {
+                    if *status != e.process.status {
+                        row.status = row.status.yellow().bold().to_string();
                     }
-                } else {
-                    // 原本就是停止状态
-                    ProcessInfo {
-                        id: p.pmr_id.to_string(),
-                        name: p.name.clone(),
-                        namespace: p.namespace.clone(),
-                        version: "N/A".to_string(),
-                        pid: "0".to_string(),
-                        uptime: "0s".to_string(),
-                        restarts: p.restarts.to_string(),
-                        status: "stopped".to_string(),
-                        cpu: "0%".to_string(),
-                        mem: "0 MB".to_string(),
-                        user: "N/A".to_string(),
+                    if e.process.restarts > *restarts {
+                        row.restarts = row.restarts.red().bold().to_string();
                     }
-                };
-                status
+                }
+                row
+            })
+            .collect();
+        previous = entries
+            .iter()
+            .map(|e| {
+                (
+                    entry_key(e, system),
+                    (e.process.status.clone(), e.process.restarts),
+                )
             })
             .collect();
 
-        let table = Table::new(processes).to_string();
-        println!("{}", table);
+        queue!(
+            stdout,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(ClearType::All),
+            Print(format!(
+                "{}\n\n",
+                t!("list.refreshing", format!("{:.1}", interval.as_secs_f64()))
+            )),
+            Print(localized_table(rows)),
+            Print("\n")
+        )?;
+        stdout.flush()?;
+
+        thread::sleep(interval);
+        DumpConfig::get_instance()
+            .reload()
+            .map_err(PmrError::Registry)?;
+        sys.refresh_processes();
+        sys.refresh_users_list();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_specs() {
+        let spec = SortSpec::from_str("cpu").unwrap();
+        assert_eq!(spec.field, SortField::Cpu);
+        assert!(!spec.descending);
+
+        let spec = SortSpec::from_str("mem:desc").unwrap();
+        assert_eq!(spec.field, SortField::Mem);
+        assert!(spec.descending);
+
+        let spec = SortSpec::from_str("Restarts:ASC").unwrap();
+        assert_eq!(spec.field, SortField::Restarts);
+        assert!(!spec.descending);
+
+        assert!(SortSpec::from_str("size").is_err());
+        assert!(SortSpec::from_str("name:up").unwrap_err().contains("up"));
+    }
+
+    #[test]
+    fn csv_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
     }
 }
@@ -1,96 +1,97 @@
 use super::super::config::dump::DumpConfig;
 use super::super::config::log;
+use super::super::error::PmrError;
+use super::target::resolve_target;
 use ctrlc;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
 use std::thread;
 use std::time::Duration;
 
-pub fn tail_log(target: String) -> io::Result<()> {
+pub fn tail_log(target: String) -> Result<(), PmrError> {
     ctrlc::set_handler(move || {
-        println!("\n退出日志查看");
+        println!("\n{}", t!("log.exit"));
         std::process::exit(0);
     })
-    .expect("无法设置Ctrl+C处理器");
+    .map_err(|e| io::Error::other(t!("ctrlc.failed", e)))?;
 
     let dump_config = DumpConfig::get_instance();
 
-    // 解析目标ID
-    let pmr_id = match target.parse::<u32>() {
-        Ok(id) => id,
-        Err(_) => {
-            // 如果不是数字，尝试按名称查找
-            match dump_config.list_processes() {
-                Ok(processes) => {
-                    if let Some(process) = processes.iter().find(|p| p.name == target) {
-                        process.pmr_id
-                    } else {
-                        eprintln!("找不到进程: {}", target);
-                        return Ok(());
-                    }
-                }
-                Err(e) => {
-                    eprintln!("无法获取进程列表: {}", e);
-                    return Ok(());
-                }
+    // 解析目标：pmr_id、name、namespace或all
+    let processes = dump_config.list_processes().map_err(PmrError::Registry)?;
+    let targets = resolve_target(&processes, &target);
+    if targets.is_empty() {
+        return Err(PmrError::NotFound(target));
+    }
+
+    let mut readers = Vec::new();
+    for process in &targets {
+        // 获取日志文件路径
+        let log_path = match log::get_log_path(process.pmr_id) {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("{}", t!("log.path_failed", e));
+                continue;
             }
-        }
-    };
+        };
 
-    // 获取日志文件路径
-    let log_path = match log::get_log_path(pmr_id) {
-        Ok(path) => path,
-        Err(e) => {
-            eprintln!("无法获取日志文件路径: {}", e);
-            return Ok(());
+        // 检查日志文件是否存在
+        if !log_path.exists() {
+            eprintln!("{}", t!("log.missing", log_path.display()));
+            continue;
         }
-    };
-
-    // 检查日志文件是否存在
-    if !log_path.exists() {
-        eprintln!("日志文件不存在: {:?}", log_path);
-        return Ok(());
-    }
 
-    println!("正在查看日志文件: {:?}", log_path);
-    println!("按 Ctrl+C 退出日志查看...");
+        // 打开日志文件
+        let mut file = match File::open(&log_path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("{}", t!("log.open_failed", e));
+                continue;
+            }
+        };
 
-    // 打开日志文件
-    let mut file = match File::open(&log_path) {
-        Ok(file) => file,
-        Err(e) => {
-            eprintln!("无法打开日志文件: {}", e);
-            return Ok(());
+        // 移动到文件末尾
+        if let Err(e) = file.seek(SeekFrom::End(0)) {
+            eprintln!("{}", t!("log.seek_failed", e));
+            continue;
         }
-    };
 
-    // 移动到文件末尾
-    if let Err(e) = file.seek(SeekFrom::End(0)) {
-        eprintln!("无法定位到文件末尾: {}", e);
+        println!("{}", t!("log.viewing", log_path.display()));
+        readers.push((process.name.clone(), BufReader::new(file)));
+    }
+
+    if readers.is_empty() {
         return Ok(());
     }
+    println!("{}", t!("log.hint"));
 
-    let mut reader = BufReader::new(file);
+    // 查看多个进程的日志时，在每行前加上进程名称
+    let show_prefix = readers.len() > 1;
     let mut buffer = String::new();
 
     // 持续读取新的日志内容
     loop {
-        match reader.read_line(&mut buffer) {
-            Ok(0) => {
-                // 没有新的内容，等待一下
-                thread::sleep(Duration::from_millis(100));
-            }
-            Ok(_) => {
-                // 打印新的内容
-                print!("{}", buffer);
-                buffer.clear();
-            }
-            Err(e) => {
-                eprintln!("读取日志时出错: {}", e);
-                break;
+        let mut has_new = false;
+        for (name, reader) in readers.iter_mut() {
+            match reader.read_line(&mut buffer) {
+                Ok(0) => {}
+                Ok(_) => {
+                    // 打印新的内容
+                    if show_prefix {
+                        print!("[{}] {}", name, buffer);
+                    } else {
+                        print!("{}", buffer);
+                    }
+                    buffer.clear();
+                    has_new = true;
+                }
+                Err(e) => return Err(e.into()),
             }
         }
-    }
 
-    Ok(())
+        if !has_new {
+            // 没有新的内容，等待一下
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
 }
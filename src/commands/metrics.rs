@@ -0,0 +1,237 @@
+use super::super::base::process::{is_same_process, PmrProcessInfo};
+use super::super::config::dump::DumpConfig;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+use sysinfo::{ProcessExt, System, SystemExt};
+
+// 读取请求的超时时间，避免单个客户端阻塞服务
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 单个进程的指标采样
+struct Sample {
+    labels: String,
+    up: bool,
+    restarts: u32,
+    cpu: f32,
+    memory: u64,
+    uptime: u64,
+    open_fds: Option<usize>,
+    last_exit_code: Option<i32>,
+}
+
+/// 导出的指标：(名称, 类型, 说明, 取值函数)，取值为 None 时不输出该进程的这一项
+type Metric = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&Sample) -> Option<f64>,
+);
+
+const METRICS: [Metric; 7] = [
+    (
+        "pmr_up",
+        "gauge",
+        "Whether the process is running (1) or not (0)",
+        |s| Some(if s.up { 1.0 } else { 0.0 }),
+    ),
+    (
+        "pmr_restarts_total",
+        "counter",
+        "Number of times the process was restarted",
+        |s| Some(s.restarts as f64),
+    ),
+    (
+        "pmr_cpu_usage_percent",
+        "gauge",
+        "CPU usage of the process in percent",
+        |s| Some(s.cpu as f64),
+    ),
+    (
+        "pmr_memory_rss_bytes",
+        "gauge",
+        "Resident memory of the process in bytes",
+        |s| Some(s.memory as f64),
+    ),
+    (
+        "pmr_uptime_seconds",
+        "gauge",
+        "Seconds since the process was started",
+        |s| Some(s.uptime as f64),
+    ),
+    (
+        "pmr_open_fds",
+        "gauge",
+        "Number of open file descriptors of the process",
+        |s| s.open_fds.map(|n| n as f64),
+    ),
+    (
+        "pmr_last_exit_code",
+        "gauge",
+        "Exit code of the last exit of the process",
+        |s| s.last_exit_code.map(|c| c as f64),
+    ),
+];
+
+/// 转义Prometheus标签值中的反斜杠、双引号和换行
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(target_os = "linux")]
+fn open_fds(pid: u32) -> Option<usize> {
+    std::fs::read_dir(format!("/proc/{}/fd", pid))
+        .ok()
+        .map(|entries| entries.count())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_fds(_pid: u32) -> Option<usize> {
+    None
+}
+
+fn sample(sys: &System, process: &PmrProcessInfo) -> Sample {
+    let labels = format!(
+        "name=\"{}\",namespace=\"{}\",pmr_id=\"{}\"",
+        escape_label(&process.name),
+        escape_label(&process.namespace),
+        process.pmr_id
+    );
+    // 确认PID没有被其他进程复用
+    let sys_proc = if process.pid > 0 && is_same_process(process.pid, process.start_time) {
+        sys.process(sysinfo::Pid::from(process.pid as usize))
+    } else {
+        None
+    };
+
+    Sample {
+        labels,
+        up: sys_proc.is_some(),
+        restarts: process.restarts,
+        cpu: sys_proc.map_or(0.0, |p| p.cpu_usage()),
+        memory: sys_proc.map_or(0, |p| p.memory()),
+        uptime: sys_proc.map_or(0, |p| p.run_time()),
+        open_fds: sys_proc.and_then(|_| open_fds(process.pid)),
+        last_exit_code: process.last_exit_code,
+    }
+}
+
+/// 以Prometheus文本格式输出所有进程的指标
+pub fn render_metrics(sys: &System) -> io::Result<String> {
+    let dump_config = DumpConfig::get_instance();
+    // 其他pmr命令可能修改了进程列表，每次都重新读取
+    dump_config.reload()?;
+    let samples: Vec<Sample> = dump_config
+        .list_processes()?
+        .iter()
+        .map(|p| sample(sys, p))
+        .collect();
+
+    let mut output = String::new();
+    for (name, kind, help, value) in METRICS {
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} {}", name, kind);
+        for s in &samples {
+            if let Some(v) = value(s) {
+                let _ = writeln!(output, "{}{{{}}} {}", name, s.labels, v);
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// 创建并采样系统信息，CPU使用率需要两次采样之间的差值
+fn new_system() -> System {
+    let mut sys = System::new();
+    sys.refresh_processes();
+    std::thread::sleep(System::MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_processes();
+    sys
+}
+
+/// 输出一次当前的指标
+pub fn print_metrics() -> io::Result<()> {
+    print!("{}", render_metrics(&new_system())?);
+    Ok(())
+}
+
+/// 在 host:port 上提供 /metrics 接口，按顺序处理请求
+pub fn serve_metrics(host: &str, port: u16) -> io::Result<()> {
+    let listener = TcpListener::bind((host, port))?;
+    println!(
+        "{}",
+        t!(
+            "metrics.started",
+            format!("http://{}:{}/metrics", host, port)
+        )
+    );
+
+    // 在多次抓取之间复用，CPU使用率为两次抓取之间的平均值
+    let mut sys = new_system();
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{}", t!("server.accept_failed", e));
+                continue;
+            }
+        };
+        sys.refresh_processes();
+        if let Err(e) = handle_request(stream, &sys) {
+            eprintln!("{}", t!("metrics.request_failed", e));
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(mut stream: TcpStream, sys: &System) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // 读完请求头，请求体不需要处理
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && !line.trim_end().is_empty() {
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => match render_metrics(sys) {
+            Ok(body) => ("200 OK", "text/plain; version=0.0.4; charset=utf-8", body),
+            Err(e) => (
+                "500 Internal Server Error",
+                "text/plain; charset=utf-8",
+                format!("{}\n", e),
+            ),
+        },
+        ("GET", _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "metrics are served at /metrics\n".to_string(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "only GET is supported\n".to_string(),
+        ),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -1,8 +1,20 @@
+pub mod api;
+pub mod daemon;
 pub mod delete;
+pub mod describe;
+pub mod events;
+pub mod health;
 pub mod list;
 pub mod log;
+pub mod metrics;
+pub mod monit;
+pub mod reload;
 pub mod restart;
+pub mod scale;
+pub mod schedule;
 pub mod start;
 pub mod stop;
+pub mod target;
+pub mod watch;
 
 pub use log::tail_log;
@@ -0,0 +1,377 @@
+use super::super::config::dump::DumpConfig;
+use super::super::config::log;
+use super::super::i18n;
+use super::list::{collect_processes, time_to_readable, ProcessEntry};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Stdout, Write};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use sysinfo::{System, SystemExt};
+
+// 每个进程保留的采样点数量
+const HISTORY_LEN: usize = 30;
+// 日志面板读取的文件末尾字节数
+const LOG_TAIL_BYTES: u64 = 16 * 1024;
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// 恢复终端状态，即使发生错误也会在退出时执行
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter(stdout: &mut Stdout) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[derive(Default)]
+struct History {
+    cpu: VecDeque<f32>,
+    mem: VecDeque<u64>,
+}
+
+impl History {
+    fn push(&mut self, cpu: f32, mem: u64) {
+        self.cpu.push_back(cpu);
+        self.mem.push_back(mem);
+        if self.cpu.len() > HISTORY_LEN {
+            self.cpu.pop_front();
+            self.mem.pop_front();
+        }
+    }
+}
+
+/// 等待确认的操作
+enum Pending {
+    None,
+    Delete(u32, String),
+}
+
+struct Monit {
+    sys: System,
+    entries: Vec<ProcessEntry>,
+    history: HashMap<u32, History>,
+    selected: usize,
+    message: String,
+    pending: Pending,
+}
+
+fn sparkline(values: impl Iterator<Item = f64>, max: f64) -> String {
+    values
+        .map(|v| {
+            if max <= 0.0 {
+                return SPARK_CHARS[0];
+            }
+            let idx = ((v / max) * (SPARK_CHARS.len() - 1) as f64).round() as usize;
+            SPARK_CHARS[idx.min(SPARK_CHARS.len() - 1)]
+        })
+        .collect()
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    line.chars().take(width).collect()
+}
+
+// 中日韩等全角字符在终端中占两列
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+// 按显示宽度补齐空格，`left` 为 false 时右对齐
+fn pad(text: &str, width: usize, left: bool) -> String {
+    let fill = " ".repeat(width.saturating_sub(display_width(text)));
+    if left {
+        format!("{}{}", text, fill)
+    } else {
+        format!("{}{}", fill, text)
+    }
+}
+
+fn read_log_tail(pmr_id: u32, lines: usize) -> Vec<String> {
+    let path = match log::get_log_path(pmr_id) {
+        Ok(path) => path,
+        Err(e) => return vec![t!("log.path_failed", e)],
+    };
+    let mut file = match File::open(&path) {
+        Ok(file) => file,
+        Err(_) => return vec![t!("log.missing", path.display())],
+    };
+
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let _ = file.seek(SeekFrom::Start(len.saturating_sub(LOG_TAIL_BYTES)));
+    let mut buf = Vec::new();
+    let _ = file.read_to_end(&mut buf);
+
+    let content = String::from_utf8_lossy(&buf);
+    let all: Vec<&str> = content.lines().collect();
+    all[all.len().saturating_sub(lines)..]
+        .iter()
+        .map(|l| l.to_string())
+        .collect()
+}
+
+impl Monit {
+    fn new() -> Self {
+        Self {
+            sys: System::new(),
+            entries: Vec::new(),
+            history: HashMap::new(),
+            selected: 0,
+            message: String::new(),
+            pending: Pending::None,
+        }
+    }
+
+    fn refresh(&mut self) {
+        // 重新读取dump.json，获取操作或其他pmr进程写入的最新状态
+        if let Err(e) = DumpConfig::get_instance().reload() {
+            self.message = t!("list.read_failed", e);
+        }
+        // System在多次刷新之间保持，才能计算出CPU使用率
+        self.sys.refresh_processes();
+        self.sys.refresh_users_list();
+        match collect_processes(&self.sys, false) {
+            Ok(entries) => self.entries = entries,
+            Err(e) => self.message = t!("list.read_failed", e),
+        }
+
+        for e in &self.entries {
+            self.history
+                .entry(e.process.pmr_id)
+                .or_default()
+                .push(e.monit.cpu, e.monit.memory);
+        }
+        let ids: Vec<u32> = self.entries.iter().map(|e| e.process.pmr_id).collect();
+        self.history.retain(|id, _| ids.contains(id));
+
+        if self.selected >= self.entries.len() {
+            self.selected = self.entries.len().saturating_sub(1);
+        }
+    }
+
+    fn selected_entry(&self) -> Option<&ProcessEntry> {
+        self.entries.get(self.selected)
+    }
+
+    /// 通过调用pmr自身执行操作，避免命令输出破坏界面
+    fn run_action(&mut self, action: &str, pmr_id: u32) {
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe,
+            Err(e) => {
+                self.message = t!("monit.action_failed", action, pmr_id, e);
+                return;
+            }
+        };
+        self.message = match Command::new(exe)
+            .args([action, &pmr_id.to_string()])
+            .env("PMR_LANG", i18n::lang().code())
+            .output()
+        {
+            Ok(output) if output.status.success() => t!("monit.action_succeeded", action, pmr_id),
+            Ok(output) => t!(
+                "monit.action_failed",
+                action,
+                pmr_id,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => t!("monit.action_failed", action, pmr_id, e),
+        };
+        self.refresh();
+    }
+
+    /// 处理按键，返回false表示退出
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        if let Pending::Delete(pmr_id, name) = &self.pending {
+            let (pmr_id, name) = (*pmr_id, name.clone());
+            self.pending = Pending::None;
+            if matches!(code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.run_action("delete", pmr_id);
+            } else {
+                self.message = t!("monit.delete_cancelled", name);
+            }
+            return true;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.entries.len() => {
+                self.selected += 1;
+            }
+            KeyCode::Char('r') => {
+                if let Some(id) = self.selected_entry().map(|e| e.process.pmr_id) {
+                    self.run_action("restart", id);
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some(id) = self.selected_entry().map(|e| e.process.pmr_id) {
+                    self.run_action("stop", id);
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(e) = self.selected_entry() {
+                    let (pmr_id, name) = (e.process.pmr_id, e.process.name.clone());
+                    self.message = t!("monit.delete_confirm", name);
+                    self.pending = Pending::Delete(pmr_id, name);
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&self, stdout: &mut Stdout) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        let mut lines: Vec<(String, bool)> = Vec::new();
+
+        lines.push(("pmr monit".to_string(), true));
+        lines.push((
+            format!(
+                "{} {} {} {:>4} {} {:<30} {} {:<30} {}",
+                pad(&t!("column.id"), 4, true),
+                pad(&t!("column.name"), 16, true),
+                pad(&t!("column.status"), 9, true),
+                "↺",
+                pad(&t!("column.cpu"), 7, false),
+                "",
+                pad(&t!("column.mem"), 9, false),
+                "",
+                pad(&t!("column.uptime"), 8, false)
+            ),
+            true,
+        ));
+
+        for (i, e) in self.entries.iter().enumerate() {
+            let history = self.history.get(&e.process.pmr_id);
+            let cpu_spark = history.map_or(String::new(), |h| {
+                sparkline(h.cpu.iter().map(|&v| v as f64), 100.0)
+            });
+            let mem_spark = history.map_or(String::new(), |h| {
+                let max = h.mem.iter().copied().max().unwrap_or(0) as f64;
+                sparkline(h.mem.iter().map(|&v| v as f64), max)
+            });
+            let marker = if i == self.selected { ">" } else { " " };
+            lines.push((
+                format!(
+                    "{}{:<3} {:<16} {:<9} {:>4} {:>6.1}% {:<30} {:>6.1} MB {:<30} {:>8}",
+                    marker,
+                    e.process.pmr_id,
+                    truncate(&e.process.name, 16),
+                    e.process.status,
+                    e.process.restarts,
+                    e.monit.cpu,
+                    cpu_spark,
+                    e.monit.memory as f64 / 1024.0 / 1024.0,
+                    mem_spark,
+                    time_to_readable(e.monit.uptime),
+                ),
+                i == self.selected,
+            ));
+        }
+
+        // 剩余空间用于日志面板，保留底部两行显示按键提示和消息
+        let used = lines.len() + 1;
+        let log_height = height.saturating_sub(used + 2);
+        if let Some(e) = self.selected_entry() {
+            lines.push((
+                format!("── {} ", t!("monit.logs", e.process.name, e.process.pmr_id)),
+                true,
+            ));
+            for line in read_log_tail(e.process.pmr_id, log_height) {
+                lines.push((line, false));
+            }
+        } else {
+            lines.push((t!("monit.empty"), false));
+        }
+
+        queue!(
+            stdout,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+        for (row, (line, highlight)) in lines.iter().take(height.saturating_sub(2)).enumerate() {
+            queue!(stdout, cursor::MoveTo(0, row as u16))?;
+            if *highlight {
+                queue!(stdout, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(
+                stdout,
+                Print(truncate(line, width)),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+
+        let footer = t!("monit.footer");
+        queue!(
+            stdout,
+            cursor::MoveTo(0, height.saturating_sub(2) as u16),
+            Print(truncate(&self.message, width)),
+            cursor::MoveTo(0, height.saturating_sub(1) as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(truncate(
+                &format!("{:<width$}", footer, width = width),
+                width
+            )),
+            SetAttribute(Attribute::Reset)
+        )?;
+        stdout.flush()
+    }
+}
+
+pub fn monit() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let _guard = TerminalGuard::enter(&mut stdout)?;
+
+    let refresh_interval = Duration::from_secs(1);
+    let mut app = Monit::new();
+    app.refresh();
+    let mut last_refresh = Instant::now();
+
+    loop {
+        app.draw(&mut stdout)?;
+
+        let timeout = refresh_interval.saturating_sub(last_refresh.elapsed());
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !app.handle_key(key.code, key.modifiers) {
+                    break;
+                }
+            }
+        }
+
+        if last_refresh.elapsed() >= refresh_interval {
+            app.refresh();
+            last_refresh = Instant::now();
+        }
+    }
+
+    Ok(())
+}
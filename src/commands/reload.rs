@@ -0,0 +1,147 @@
+use super::super::base::child::untrack_child;
+use super::super::base::health::run_health_check;
+use super::super::base::hook::{run_hook, HookStage};
+use super::super::base::process::PmrProcessInfo;
+use super::super::base::ready::{parse_ready_signal, ReadySignal, ReadyWaiter};
+use super::super::base::user::{process_credentials, Credentials};
+use super::super::config::dump::DumpConfig;
+use super::super::error::PmrError;
+use super::list::list_processes;
+use super::restart::{notify_restart, restart_existing_process};
+use super::start::spawn_process;
+use super::stop::stop_gracefully;
+use super::target::resolve_target;
+use std::io;
+use std::process::Child;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 新实例需要持续运行的时间，超过后才认为已就绪并停止旧实例
+const READY_DELAY: Duration = Duration::from_millis(1000);
+// 等待新实例健康检查通过时两次检查的间隔
+const HEALTH_RETRY: Duration = Duration::from_millis(200);
+
+/// 滚动重启：逐个启动新实例，新实例就绪后再停止对应的旧实例，
+/// 任意一个新实例启动失败时中止，剩余的旧实例保持运行
+pub fn reload_process(target: &str) -> Result<(), PmrError> {
+    let dump_config = DumpConfig::get_instance();
+    let processes = dump_config.list_processes().map_err(PmrError::Registry)?;
+
+    let mut targets = resolve_target(&processes, target);
+    if targets.is_empty() {
+        return Err(PmrError::NotFound(target.to_string()));
+    }
+    targets.sort_by_key(|p| (p.name.clone(), p.instance_id.unwrap_or(0), p.pmr_id));
+
+    for (index, process) in targets.iter().enumerate() {
+        if let Err(e) = reload_existing_process(process) {
+            let remaining = targets.len() - index - 1;
+            eprintln!(
+                "{}",
+                t!("reload.aborted", process.name, process.pmr_id, remaining)
+            );
+            let _ = list_processes(false);
+            return Err(PmrError::restart_failed(process, e));
+        }
+    }
+
+    list_processes(false)
+}
+
+/// 先启动新进程并等待就绪，再停止旧进程，重载期间始终有实例在运行
+fn reload_existing_process(process: &PmrProcessInfo) -> io::Result<()> {
+    // 旧进程已经不在运行时没有需要保持的服务，直接重启
+    if !process.is_alive() {
+        let pid = restart_existing_process(process, "reload")?;
+        println!("{}", t!("reload.reloaded", process.name, pid));
+        return Ok(());
+    }
+
+    // 旧进程仍在监听同一个端口、写入同一个日志文件，也可能更新就绪文件，
+    // 这些就绪信号无法区分新旧进程。notify 使用每个新进程单独的socket，不受影响
+    if let Some(spec) = &process.wait_ready {
+        if let Ok(ReadySignal::Port(_) | ReadySignal::Log(_) | ReadySignal::File(_)) =
+            parse_ready_signal(spec)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                t!("reload.ambiguous_ready", spec),
+            ));
+        }
+    }
+
+    println!("{}", t!("reload.reloading", process.name));
+    let credentials = process_credentials(process)?;
+    run_hook(process, HookStage::PreStart, credentials.as_ref()).map_err(io::Error::other)?;
+    let mut waiter = ReadyWaiter::prepare(process, credentials.as_ref())?;
+    let mut child = spawn_process(process, waiter.as_ref(), credentials.as_ref())?;
+    let pid = child.id();
+
+    let ready = match waiter.as_mut() {
+        // 重载时超时也视为失败，保留旧进程
+        Some(waiter) => waiter
+            .wait(&mut child, Duration::from_millis(process.listen_timeout))
+            .map_err(|e| e.to_string()),
+        None => wait_alive(&mut child),
+    }
+    .and_then(|_| wait_healthy(process, &mut child, credentials.as_ref()));
+    if let Err(e) = ready {
+        eprintln!("{}", t!("reload.not_ready", process.name, pid, e));
+        let _ = child.kill();
+        let _ = child.wait();
+        untrack_child(pid);
+        return Err(io::Error::other(e));
+    }
+
+    stop_gracefully(process).map_err(io::Error::other)?;
+
+    let dump_config = DumpConfig::get_instance();
+    dump_config.update_process_status(process.pmr_id, pid, "running".to_string())?;
+    dump_config.increment_restarts(process.pmr_id, "reload")?;
+    notify_restart(process, pid, "reload");
+    println!("{}", t!("reload.reloaded", process.name, pid));
+    if let Err(e) = run_hook(process, HookStage::PostStart, credentials.as_ref()) {
+        eprintln!("{}", t!("hook.process_failed", process.name, e));
+    }
+    Ok(())
+}
+
+/// 配置了健康检查时，新进程需要在 listen_timeout 内通过检查才视为就绪
+fn wait_healthy(
+    process: &PmrProcessInfo,
+    child: &mut Child,
+    credentials: Option<&Credentials>,
+) -> Result<(), String> {
+    let Some(check) = &process.health_check else {
+        return Ok(());
+    };
+    let deadline = Instant::now() + Duration::from_millis(process.listen_timeout);
+    loop {
+        let result = run_health_check(check, &process.workdir, child.id(), credentials);
+        match child.try_wait() {
+            Ok(Some(status)) => return Err(t!("reload.exited", status)),
+            Ok(None) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        match result {
+            Ok(_) => return Ok(()),
+            Err(e) if Instant::now() >= deadline => {
+                return Err(t!("reload.unhealthy", process.listen_timeout, e))
+            }
+            Err(_) => thread::sleep(HEALTH_RETRY),
+        }
+    }
+}
+
+/// 未配置 wait_ready 时，新进程持续运行 READY_DELAY 后视为就绪
+fn wait_alive(child: &mut Child) -> Result<(), String> {
+    let deadline = Instant::now() + READY_DELAY;
+    while Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(Some(status)) => return Err(t!("reload.exited", status)),
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(())
+}
@@ -1,96 +1,153 @@
-use super::super::base::process::PmrProcessInfo;
+use super::super::base::event::{emit, Event, EventKind};
+use super::super::base::process::{unix_now, PmrProcessInfo};
 use super::super::config::dump::DumpConfig;
-use super::super::config::log;
+use super::super::error::PmrError;
+use super::super::manager::ProcessManager;
 use super::list::list_processes;
-use super::start::start_process;
-use super::stop::stop_process;
-use std::fs::OpenOptions;
+use super::start::{print_ready_wait, spawn_until_ready, start_process, StartOptions};
+use super::stop::stop_gracefully;
+use super::target::{current_lines, print_batch};
 use std::io;
 use std::path::PathBuf;
-use std::process::Stdio;
+
+// 在 CRASH_LOOP_WINDOW 秒内意外退出或因健康检查失败重启达到 CRASH_LOOP_FAILURES 次视为崩溃循环
+const CRASH_LOOP_FAILURES: usize = 5;
+const CRASH_LOOP_WINDOW: u64 = 60;
+// 计入崩溃循环的重启原因，pmr 主动发起的重启（手动、watch、cron、reload、内存限制）不计入
+const FAILURE_RESTART_REASONS: &[&str] = &["health check"];
 
 pub fn restart_process(
     config: Option<PathBuf>,
     namespace: Option<String>,
     target: Option<String>,
     args: Vec<String>,
-) -> io::Result<()> {
-    let dump_config = DumpConfig::get_instance();
-
-    // 如果指定了target，先检查是否是已存在的进程
+) -> Result<(), PmrError> {
+    // 如果指定了target，先检查是否是已存在的进程（pmr_id、name、namespace或all）
     if let Some(ref target_str) = target {
-        if let Ok(processes) = dump_config.list_processes() {
-            // 尝试将target解析为pmr_id
-            if let Ok(pmr_id) = target_str.parse::<u32>() {
-                if let Some(process) = processes.iter().find(|p| p.pmr_id == pmr_id) {
-                    restart_existing_process(process)?;
-                    return Ok(());
-                }
-            }
+        let manager = ProcessManager::new()?;
+        match manager.find(target_str) {
+            Ok(targets) => {
+                targets.iter().for_each(print_ready_wait);
+                let result = manager.restart(target_str);
+                let pmr_ids: Vec<u32> = targets.iter().map(|p| p.pmr_id).collect();
+                let lines = current_lines(&pmr_ids, |p| t!("restart.restarted", p.name, p.pid))?;
+                print_batch(&t!("restart.action"), &lines, &result);
 
-            // 按名称查找进程
-            if let Some(process) = processes.iter().find(|p| p.name == *target_str) {
-                restart_existing_process(process)?;
-                return Ok(());
+                // 显示进程列表
+                list_processes(false)?;
+                return result.map(|_| ());
             }
+            Err(PmrError::NotFound(_)) => {}
+            Err(e) => return Err(e),
         }
     }
 
     // 如果不是重启已存在的进程，就当作普通的启动处理
-    start_process(config, namespace, "default".to_string(), target, args)?;
-    Ok(())
+    start_process(
+        config,
+        None,
+        namespace.unwrap_or_else(|| "default".to_string()),
+        target,
+        args,
+        StartOptions::default(),
+    )
 }
 
-fn restart_existing_process(process: &PmrProcessInfo) -> io::Result<()> {
-    println!("正在重启进程 '{}'...", process.name);
-
-    // 先停止进程
-    stop_process(&process.pmr_id.to_string(), false);
+/// 发出 restart 事件，健康检查失败引起的重启还会检查是否进入崩溃循环
+pub(crate) fn notify_restart(process: &PmrProcessInfo, pid: u32, reason: &str) {
+    let event = Event::new(EventKind::Restart, process, reason).with_pid(pid);
+    emit(event.clone());
+    if FAILURE_RESTART_REASONS.contains(&reason) {
+        check_crash_loop(process, &event);
+    }
+}
 
-    // 获取日志文件路径
-    let log_path = log::get_log_path(process.pmr_id)?;
+/// 记录为运行中但已经不存在的进程视为意外退出
+pub(crate) fn is_crashed(process: &PmrProcessInfo) -> bool {
+    process.status == "running" && process.pid > 0 && !process.is_alive()
+}
 
-    // 打开日志文件（追加模式）
-    let log_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)?;
+/// 记录进程意外退出并发出 exit 事件，检查是否进入崩溃循环。
+/// 其他pmr命令已经记录了这次退出时不再重复发出事件
+pub(crate) fn record_crash(
+    process: &PmrProcessInfo,
+    code: Option<i32>,
+    signal: Option<i32>,
+) -> io::Result<()> {
+    if !DumpConfig::get_instance().record_exit(process.pmr_id, process.pid, code)? {
+        return Ok(());
+    }
+    let mut event = Event::new(EventKind::Exit, process, "");
+    event.exit_code = code;
+    event.signal = signal;
+    emit(event.clone());
+    check_crash_loop(process, &event);
+    Ok(())
+}
 
-    // 使用同一个文件句柄来重定向标准输出和标准错误
-    let stdout_log = log_file.try_clone()?;
-    let stderr_log = log_file.try_clone()?;
+/// 短时间内意外退出和健康检查失败引起的重启次数过多时发出 crash-loop 事件，
+/// 调用前需要已经记录本次退出或重启，last 为本次的 exit 或 restart 事件
+fn check_crash_loop(process: &PmrProcessInfo, last: &Event) {
+    let Ok(processes) = DumpConfig::get_instance().list_processes() else {
+        return;
+    };
+    let Some(latest) = processes.iter().find(|p| p.pmr_id == process.pmr_id) else {
+        return;
+    };
+    let since = unix_now().saturating_sub(CRASH_LOOP_WINDOW);
+    let restarts = latest
+        .restart_history
+        .iter()
+        .filter(|r| FAILURE_RESTART_REASONS.contains(&r.reason.as_str()) && r.timestamp >= since)
+        .count();
+    let exits = latest
+        .status_history
+        .iter()
+        .filter(|s| s.status == "exited" && s.timestamp >= since)
+        .count();
+    let failures = restarts + exits;
+    // 只在刚达到阈值时发出一次
+    if failures == CRASH_LOOP_FAILURES {
+        // 最近一次失败是退出时原因记为 exit，并带上退出状态
+        let reason = if last.event == EventKind::Exit.name() {
+            EventKind::Exit.name()
+        } else {
+            last.reason.as_str()
+        };
+        let mut event = Event::new(EventKind::CrashLoop, process, reason).with_pid(last.pid);
+        event.exit_code = last.exit_code;
+        event.signal = last.signal;
+        event.failures = Some(failures as u32);
+        event.window = Some(CRASH_LOOP_WINDOW);
+        emit(event);
+    }
+}
 
-    // 重新启动进程
-    let mut cmd = std::process::Command::new(&process.program);
-    cmd.args(&process.args)
-        .stdout(Stdio::from(stdout_log))
-        .stderr(Stdio::from(stderr_log));
+/// 重启进程并打印结果，用于 daemon 的自动重启
+pub(crate) fn restart_and_report(process: &PmrProcessInfo, reason: &str) {
+    match restart_existing_process(process, reason) {
+        Ok(pid) => println!("{}", t!("restart.restarted", process.name, pid)),
+        Err(e) => eprintln!("{}", t!("restart.failed", process.name, e)),
+    }
+}
 
-    match cmd.spawn() {
-        Ok(child) => {
-            let pid = child.id();
-            println!("进程 '{}' 重启成功，新 PID: {}", process.name, pid);
+/// 重启进程并返回新进程的PID，reason 会记录在重启历史中（例如 "manual"、"memory limit"）
+pub(crate) fn restart_existing_process(process: &PmrProcessInfo, reason: &str) -> io::Result<u32> {
+    // 守护进程没有运行或者还没有发现进程已经退出，重启前同样计入崩溃循环
+    if is_crashed(process) {
+        record_crash(process, None, None)?;
+    }
 
-            let dump_config = DumpConfig::get_instance();
-            dump_config
-                .update_process_status(process.pmr_id, pid, "running".to_string())
-                .expect("无法更新进程状态");
+    // 先停止进程，给进程机会处理SIGTERM后再强制结束
+    stop_gracefully(process).map_err(io::Error::other)?;
 
-            // 增加重启次数
-            dump_config
-                .increment_restarts(process.pmr_id)
-                .expect("无法更新重启次数");
+    // 重新启动进程
+    let pid = spawn_until_ready(process)?;
 
-            // 显示进程列表
-            list_processes(false);
-            Ok(())
-        }
-        Err(e) => {
-            eprintln!("重启进程失败: {}", e);
-            Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("重启进程失败: {}", e),
-            ))
-        }
-    }
+    // 增加重启次数
+    DumpConfig::get_instance()
+        .increment_restarts(process.pmr_id, reason)
+        .map_err(|e| io::Error::other(t!("restart.update_restarts_failed", e)))?;
+    notify_restart(process, pid, reason);
+    Ok(pid)
 }
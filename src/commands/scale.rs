@@ -0,0 +1,133 @@
+use super::super::base::event::{emit, Event, EventKind};
+use super::super::base::process::PmrProcessInfo;
+use super::super::config::dump::DumpConfig;
+use super::super::error::PmrError;
+use super::list::list_processes;
+use super::start::{launch_new_process, print_started};
+use super::stop::stop_gracefully;
+
+/// 目标实例数量：绝对值或相对当前数量的增减
+enum ScaleTarget {
+    Absolute(u32),
+    Relative(i64),
+}
+
+fn parse_scale_target(value: &str) -> Result<ScaleTarget, String> {
+    let invalid = || t!("scale.invalid_count", value);
+    if let Some(n) = value.strip_prefix('+') {
+        // "+" 后只能是数字，"+-1"、"++1" 都无效
+        n.parse::<u32>()
+            .map(|n| ScaleTarget::Relative(i64::from(n)))
+            .map_err(|_| invalid())
+    } else if value.starts_with('-') {
+        value
+            .parse::<i64>()
+            .map(ScaleTarget::Relative)
+            .map_err(|_| invalid())
+    } else {
+        value
+            .parse::<u32>()
+            .map(ScaleTarget::Absolute)
+            .map_err(|_| invalid())
+    }
+}
+
+/// 调整同名进程的实例数量，扩容时复用已有实例的程序、参数、工作目录和环境变量，
+/// 缩容时先停止实例编号最大的进程
+pub fn scale_process(name: &str, count: &str) -> Result<(), PmrError> {
+    let target = parse_scale_target(count).map_err(PmrError::InvalidArgument)?;
+
+    let dump_config = DumpConfig::get_instance();
+    let mut group: Vec<PmrProcessInfo> = dump_config
+        .list_processes()
+        .map_err(PmrError::Registry)?
+        .into_iter()
+        .filter(|p| p.name == name)
+        .collect();
+    if group.is_empty() {
+        return Err(PmrError::NotFound(name.to_string()));
+    }
+    group.sort_by_key(|p| (p.instance_id.unwrap_or(0), p.pmr_id));
+
+    let current = group.len() as i64;
+    let desired = match target {
+        ScaleTarget::Absolute(n) => n as i64,
+        ScaleTarget::Relative(delta) => (current + delta).max(0),
+    };
+
+    if desired == current {
+        println!("{}", t!("scale.unchanged", name, current));
+        return Ok(());
+    }
+
+    if desired > current {
+        let template = group[0].clone();
+        let first_instance = group
+            .iter()
+            .filter_map(|p| p.instance_id)
+            .max()
+            .map_or(group.len() as u32, |max| max + 1);
+
+        for instance_id in first_instance..first_instance + (desired - current) as u32 {
+            // 复制全部配置，只重置运行时状态
+            let mut instance = template.clone();
+            instance.pmr_id = 0;
+            instance.pid = 0;
+            instance.status = "starting".to_string();
+            instance.start_time = 0;
+            instance.restarts = 0;
+            instance.status_history.clear();
+            instance.restart_history.clear();
+            instance.health = None;
+            instance.last_exit_code = None;
+            instance.instance_id = Some(instance_id);
+
+            let pmr_id = launch_new_process(instance)?;
+            print_started(&[pmr_id])?;
+        }
+        println!("{}", t!("scale.up", name, current, desired));
+    } else {
+        // 从实例编号最大的开始停止并删除
+        for process in group.iter().rev().take((current - desired) as usize) {
+            let running = process.is_alive();
+            stop_gracefully(process).map_err(|e| PmrError::stop_failed(process, e))?;
+            if running {
+                emit(Event::new(EventKind::Stop, process, "scaled down"));
+            }
+            dump_config
+                .delete_process(process.pmr_id)
+                .map_err(|e| PmrError::delete_failed(process, e))?;
+        }
+        println!("{}", t!("scale.down", name, current, desired));
+    }
+
+    list_processes(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_targets() {
+        assert!(matches!(
+            parse_scale_target("4"),
+            Ok(ScaleTarget::Absolute(4))
+        ));
+        assert!(matches!(
+            parse_scale_target("0"),
+            Ok(ScaleTarget::Absolute(0))
+        ));
+        assert!(matches!(
+            parse_scale_target("+2"),
+            Ok(ScaleTarget::Relative(2))
+        ));
+        assert!(matches!(
+            parse_scale_target("-3"),
+            Ok(ScaleTarget::Relative(-3))
+        ));
+        for value in ["", "+", "-", "two", "1.5", "+-1"] {
+            assert!(parse_scale_target(value).is_err(), "{}", value);
+        }
+    }
+}
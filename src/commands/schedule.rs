@@ -0,0 +1,51 @@
+use super::super::base::schedule::next_fire;
+use super::super::config::dump::DumpConfig;
+use super::super::error::PmrError;
+use super::list::{localized_table, time_to_readable};
+use chrono::Local;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+struct ScheduleInfo {
+    id: String,
+    name: String,
+    cron: String,
+    status: String,
+    next: String,
+    #[tabled(rename = "in")]
+    remaining: String,
+}
+
+/// 显示每个设置了 cron_restart 的进程的下一次重启时间
+pub fn list_schedules() -> Result<(), PmrError> {
+    let dump_config = DumpConfig::get_instance();
+    let processes = dump_config.list_processes().map_err(PmrError::Registry)?;
+
+    let now = Local::now();
+    let rows: Vec<ScheduleInfo> = processes
+        .iter()
+        .filter_map(|p| {
+            let expr = p.cron_restart.as_ref()?;
+            let next = next_fire(expr, &now);
+            Some(ScheduleInfo {
+                id: p.pmr_id.to_string(),
+                name: p.name.clone(),
+                cron: expr.clone(),
+                status: p.status.clone(),
+                next: next.map_or("N/A".to_string(), |n| {
+                    n.format("%Y-%m-%d %H:%M:%S").to_string()
+                }),
+                remaining: next.map_or("N/A".to_string(), |n| {
+                    time_to_readable((n - now).num_seconds().max(0) as u64)
+                }),
+            })
+        })
+        .collect();
+
+    if rows.is_empty() {
+        println!("{}", t!("schedule.empty"));
+        return Ok(());
+    }
+    println!("{}", localized_table(rows));
+    Ok(())
+}
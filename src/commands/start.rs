@@ -1,20 +1,282 @@
+use super::super::base::child::{track_child, untrack_child};
+use super::super::base::dependency::dependency_order;
+use super::super::base::event::{emit, Event, EventKind};
+use super::super::base::health::{
+    run_health_check, validate_health_check, HealthCheck, DEFAULT_HEALTH_INTERVAL,
+    DEFAULT_HEALTH_THRESHOLD, DEFAULT_HEALTH_TIMEOUT,
+};
+use super::super::base::hook::{run_hook, Hook, HookStage, Hooks, DEFAULT_HOOK_TIMEOUT};
+use super::super::base::memory::parse_memory_size;
 use super::super::base::process::PmrProcessInfo;
+use super::super::base::ready::{validate_ready_signal, ReadyError, ReadyWaiter};
+use super::super::base::schedule::validate_cron;
+use super::super::base::user::{
+    apply_credentials, process_credentials, validate_group, validate_user, Credentials,
+};
 use super::super::config::dump::DumpConfig;
 use super::super::config::log;
+use super::super::error::PmrError;
+use super::super::manager::{ProcessManager, ProcessSpec};
 use super::list::list_processes;
-use super::stop::stop_process;
+use super::restart::{is_crashed, record_crash};
+use super::stop::stop_existing_process;
+use super::target::{current_lines, print_batch};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
-use std::fs::{File, OpenOptions};
-use std::io::{self, Read};
+use std::fs::{self, OpenOptions};
+use std::io;
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 配置文件可以是单个应用，也可以是包含多个应用的 `{"apps": [...]}`
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum ConfigFile {
+    Ecosystem { apps: Vec<Config> },
+    App(Box<Config>),
+}
 
 #[derive(Deserialize, Serialize)]
 struct Config {
     name: String,
     program: String,
     args: Vec<String>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    max_memory_restart: Option<String>,
+    #[serde(default)]
+    cron_restart: Option<String>,
+    #[serde(default)]
+    watch: Option<WatchConfig>,
+    #[serde(default)]
+    ignore_watch: Option<Vec<String>>,
+    #[serde(default)]
+    watch_delay: Option<u64>,
+    #[serde(default)]
+    instances: Option<InstancesConfig>,
+    #[serde(default)]
+    wait_ready: Option<String>,
+    #[serde(default)]
+    listen_timeout: Option<u64>,
+    #[serde(default)]
+    health_check: Option<String>,
+    #[serde(default)]
+    health_status: Option<u16>,
+    #[serde(default)]
+    health_interval: Option<u64>,
+    #[serde(default)]
+    health_timeout: Option<u64>,
+    #[serde(default)]
+    health_threshold: Option<u32>,
+    #[serde(default)]
+    health_restart: Option<bool>,
+    #[serde(default)]
+    depends_on: Vec<String>, // 需要先启动的应用名称
+    #[serde(default)]
+    hooks: HooksConfig,
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    groups: Vec<String>, // 附加组
+}
+
+#[derive(Deserialize, Serialize, Default)]
+struct HooksConfig {
+    #[serde(default)]
+    pre_start: Option<HookConfig>,
+    #[serde(default)]
+    post_start: Option<HookConfig>,
+    #[serde(default)]
+    pre_stop: Option<HookConfig>,
+    #[serde(default)]
+    post_stop: Option<HookConfig>,
+}
+
+/// 配置文件中的钩子可以是命令字符串或 `{"command": ..., "timeout": ...}`
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum HookConfig {
+    Command(String),
+    Full {
+        command: String,
+        #[serde(default)]
+        timeout: Option<u64>,
+    },
+}
+
+impl From<HookConfig> for Hook {
+    fn from(config: HookConfig) -> Self {
+        match config {
+            HookConfig::Command(command) => Hook {
+                command,
+                timeout: DEFAULT_HOOK_TIMEOUT,
+            },
+            HookConfig::Full { command, timeout } => Hook {
+                command,
+                timeout: timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT),
+            },
+        }
+    }
+}
+
+/// 配置文件中的 instances 可以是数字或 "max"
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum InstancesConfig {
+    Count(u32),
+    Text(String),
+}
+
+/// 配置文件中的 watch 可以是布尔值或路径列表
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum WatchConfig {
+    Enabled(bool),
+    Paths(Vec<String>),
+}
+
+/// 命令行中指定的应用选项，未指定时使用配置文件中的值
+#[derive(Default)]
+pub struct StartOptions {
+    pub max_memory_restart: Option<u64>,
+    pub cron_restart: Option<String>,
+    pub watch: Option<Vec<String>>, // Some表示开启监听，列表为监听路径
+    pub ignore_watch: Vec<String>,
+    pub watch_delay: Option<u64>,
+    pub instances: Option<u32>, // 集群模式的实例数量
+    pub wait_ready: Option<String>,
+    pub listen_timeout: Option<u64>,
+    pub health_check: Option<String>,
+    pub health_status: Option<u16>,
+    pub health_interval: Option<u64>,
+    pub health_timeout: Option<u64>,
+    pub health_threshold: Option<u32>,
+    pub health_restart: bool,
+    pub pre_start: Option<String>,
+    pub post_start: Option<String>,
+    pub pre_stop: Option<String>,
+    pub post_stop: Option<String>,
+    pub hook_timeout: Option<u64>,
+    pub user: Option<String>,
+    pub group: Option<String>,
+    pub groups: Vec<String>,
+}
+
+impl StartOptions {
+    /// 将命令行选项应用到直接启动的程序
+    pub(crate) fn apply(&self, process: &mut PmrProcessInfo) {
+        process.max_memory_restart = self.max_memory_restart;
+        process.cron_restart = self.cron_restart.clone();
+        self.apply_watch(process);
+        self.apply_ready(process);
+        self.apply_health(process);
+        self.apply_hooks(process);
+        self.apply_user(process);
+    }
+
+    fn apply_watch(&self, process: &mut PmrProcessInfo) {
+        if let Some(paths) = &self.watch {
+            process.watch = true;
+            process.watch_paths = paths.iter().filter(|p| !p.is_empty()).cloned().collect();
+        }
+        if !self.ignore_watch.is_empty() {
+            process.ignore_watch = self.ignore_watch.clone();
+        }
+        if let Some(delay) = self.watch_delay {
+            process.watch_delay = delay;
+        }
+    }
+
+    fn apply_ready(&self, process: &mut PmrProcessInfo) {
+        if let Some(signal) = &self.wait_ready {
+            process.wait_ready = Some(signal.clone());
+        }
+        if let Some(timeout) = self.listen_timeout {
+            process.listen_timeout = timeout;
+        }
+    }
+
+    fn apply_health(&self, process: &mut PmrProcessInfo) {
+        if let Some(check) = &self.health_check {
+            process.health_check = Some(new_health_check(check.clone()));
+        }
+        if let Some(health) = process.health_check.as_mut() {
+            if let Some(status) = self.health_status {
+                health.expect_status = Some(status);
+            }
+            if let Some(interval) = self.health_interval {
+                health.interval = interval;
+            }
+            if let Some(timeout) = self.health_timeout {
+                health.timeout = timeout;
+            }
+            if let Some(threshold) = self.health_threshold {
+                health.threshold = threshold;
+            }
+            if self.health_restart {
+                health.auto_restart = true;
+            }
+        }
+    }
+
+    fn apply_hooks(&self, process: &mut PmrProcessInfo) {
+        let timeout = self.hook_timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT);
+        let hook = |command: &Option<String>| {
+            command.as_ref().map(|command| Hook {
+                command: command.clone(),
+                timeout,
+            })
+        };
+        let hooks = &mut process.hooks;
+        hooks.pre_start = hook(&self.pre_start).or(hooks.pre_start.take());
+        hooks.post_start = hook(&self.post_start).or(hooks.post_start.take());
+        hooks.pre_stop = hook(&self.pre_stop).or(hooks.pre_stop.take());
+        hooks.post_stop = hook(&self.post_stop).or(hooks.post_stop.take());
+    }
+
+    fn apply_user(&self, process: &mut PmrProcessInfo) {
+        if let Some(user) = &self.user {
+            process.user = Some(user.clone());
+        }
+        if let Some(group) = &self.group {
+            process.group = Some(group.clone());
+        }
+        if !self.groups.is_empty() {
+            process.groups = self.groups.clone();
+        }
+    }
+}
+
+fn new_health_check(check: String) -> HealthCheck {
+    HealthCheck {
+        check,
+        expect_status: None,
+        interval: DEFAULT_HEALTH_INTERVAL,
+        timeout: DEFAULT_HEALTH_TIMEOUT,
+        threshold: DEFAULT_HEALTH_THRESHOLD,
+        auto_restart: false,
+    }
+}
+
+/// 解析实例数量，`max` 或 `0` 表示CPU核心数
+pub fn parse_instances(value: &str) -> Result<u32, String> {
+    let cpus = || {
+        std::thread::available_parallelism()
+            .map(|n| n.get() as u32)
+            .unwrap_or(1)
+    };
+    match value.trim() {
+        "max" | "0" => Ok(cpus()),
+        n => n
+            .parse::<u32>()
+            .map_err(|_| t!("start.invalid_instances", value)),
+    }
 }
 
 pub fn start_process(
@@ -23,8 +285,8 @@ pub fn start_process(
     namespace: String,
     target: Option<String>,
     args: Vec<String>,
-) -> io::Result<()> {
-    let dump_config = DumpConfig::get_instance();
+    options: StartOptions,
+) -> Result<(), PmrError> {
     // 获取当前工作目录
     let workdir = env::current_dir()
         .unwrap_or_else(|_| PathBuf::from("."))
@@ -32,160 +294,354 @@ pub fn start_process(
         .to_string();
 
     // 获取进程名称
-    let process_name = name.unwrap_or_else(|| {
+    let process_name = name.clone().unwrap_or_else(|| {
         target
             .as_ref()
-            .map(|s| s.split('/').last().unwrap_or(s))
+            .map(|s| s.split('/').next_back().unwrap_or(s))
             .unwrap_or("unknown")
             .to_string()
     });
 
-    // 如果指定了target，先检查是否是已存在的进程
+    // 如果指定了target，先检查是否是已存在的进程（pmr_id、name、namespace或all）
+    let manager = ProcessManager::new()?;
     if let Some(ref target_str) = target {
-        if let Ok(processes) = dump_config.list_processes() {
-            // 尝试将target解析为pmr_id
-            if let Ok(pmr_id) = target_str.parse::<u32>() {
-                if let Some(process) = processes.iter().find(|p| p.pmr_id == pmr_id) {
-                    start_existing_process(process)?;
-                    return Ok(());
+        match manager.find(target_str) {
+            Ok(targets) => {
+                let running: Vec<u32> = targets
+                    .iter()
+                    .filter(|p| is_already_running(p))
+                    .map(|p| p.pmr_id)
+                    .collect();
+                for process in targets.iter().filter(|p| !running.contains(&p.pmr_id)) {
+                    print_ready_wait(process);
                 }
+                let result = manager.start_existing(target_str);
+                let pmr_ids: Vec<u32> = start_order(targets).iter().map(|p| p.pmr_id).collect();
+                let lines = current_lines(&pmr_ids, |p| {
+                    if running.contains(&p.pmr_id) {
+                        t!("start.already_running", p.name, p.pid)
+                    } else {
+                        t!("start.started", p.name, p.pid)
+                    }
+                })?;
+                print_batch(&t!("start.action"), &lines, &result);
+
+                // 显示进程列表
+                list_processes(false)?;
+                return result.map(|_| ());
             }
-
-            // 按名称查找进程
-            if let Some(process) = processes.iter().find(|p| p.name == *target_str) {
-                start_existing_process(process)?;
-                return Ok(());
-            }
+            Err(PmrError::NotFound(_)) => {}
+            Err(e) => return Err(e),
         }
     }
 
+    // target 是已存在的 .json 文件时当作配置文件，例如 pmr start ecosystem.json
+    let config = config.or_else(|| {
+        target
+            .as_ref()
+            .map(PathBuf::from)
+            .filter(|p| p.extension().is_some_and(|e| e == "json") && p.is_file())
+    });
+
     // 如果指定了配置文件，从配置文件启动
     if let Some(config_path) = config {
-        if !config_path.exists() {
-            eprintln!("配置文件不存在: {:?}", config_path);
-            return Ok(());
-        }
-
         // 读取配置文件
-        let mut file = File::open(&config_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-
-        let config: Config = serde_json::from_str(&contents).expect("无法解析配置文件");
-
-        let new_id = dump_config.add_process(
-            process_name.clone(),
-            namespace.clone(),
-            workdir.clone(),
-            config.program.clone(),
-            0,
-            "starting".to_string(),
-            config.args.clone(),
-        )?;
-
-        // 获取日志文件路径
-        let log_path = log::get_log_path(new_id)?;
-
-        // 打开日志文件（追加模式）
-        let log_file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)?;
-
-        // 使用同一个文件句柄来重定向标准输出和标准错误
-        let stdout_log = log_file.try_clone()?;
-        let stderr_log = log_file.try_clone()?;
-
-        // 启动进程
-        let mut cmd = Command::new(&config.program);
-        cmd.args(&config.args)
-            .stdout(Stdio::from(stdout_log))
-            .stderr(Stdio::from(stderr_log));
-
-        match cmd.spawn() {
-            Ok(child) => {
-                let pid = child.id();
-                println!("启动进程 '{}' PID: {}", process_name, pid);
-
-                dump_config
-                    .update_process_status(new_id, pid, "running".to_string())
-                    .expect("无法更新进程状态");
-
-                list_processes(false);
+        let contents = fs::read_to_string(&config_path)
+            .map_err(|e| PmrError::Config(t!("config.read_failed", config_path.display(), e)))?;
+        let config_file: ConfigFile = serde_json::from_str(&contents)
+            .map_err(|e| PmrError::Config(t!("config.parse_failed", config_path.display(), e)))?;
+        let source = config_path
+            .canonicalize()
+            .unwrap_or(config_path)
+            .to_string_lossy()
+            .to_string();
+
+        match config_file {
+            ConfigFile::Ecosystem { apps } => {
+                start_ecosystem(apps, namespace, workdir, &source, &options)?;
             }
-            Err(e) => {
-                eprintln!("启动进程失败: {}", e);
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("启动进程失败: {}", e),
-                ));
+            ConfigFile::App(config) => {
+                let mut config = *config;
+                // 未通过 --name 指定名称时使用配置文件中的名称
+                if let Some(name) = name {
+                    config.name = name;
+                }
+                let (process, instances) =
+                    process_from_config(config, namespace, workdir, Some(&source), &options)?;
+                start_new_processes(process, instances)?;
             }
         }
     } else if let Some(target_program) = target {
         // 直接启动程序
-        let new_id = dump_config.add_process(
-            process_name.clone(),
-            namespace.clone(),
-            workdir.clone(),
-            target_program.clone(),
-            0,
-            "starting".to_string(),
-            args.clone(),
-        )?;
-
-        // 获取日志文件路径
-        let log_path = log::get_log_path(new_id)?;
-
-        // 打开日志文件（追加模式）
-        let log_file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)?;
-
-        // 使用同一个文件句柄来重定向标准输出和标准错误
-        let stdout_log = log_file.try_clone()?;
-        let stderr_log = log_file.try_clone()?;
-
-        // 启动进程
-        let mut cmd = Command::new(&target_program);
-        cmd.args(&args)
-            .stdout(Stdio::from(stdout_log))
-            .stderr(Stdio::from(stderr_log));
-
-        match cmd.spawn() {
-            Ok(child) => {
-                let pid = child.id();
-                println!("启动进程 '{}' PID: {}", process_name, pid);
-
-                dump_config
-                    .update_process_status(new_id, pid, "running".to_string())
-                    .expect("无法更新进程状态");
-                list_processes(false);
-            }
-            Err(e) => {
-                eprintln!("启动进程失败: {}", e);
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("启动进程失败: {}", e),
-                ));
-            }
+        if options.wait_ready.is_some() {
+            println!("{}", t!("ready.waiting", process_name));
         }
+        let mut spec = ProcessSpec::new(target_program);
+        spec.args = args;
+        spec.name = Some(process_name);
+        spec.namespace = Some(namespace);
+        spec.workdir = Some(workdir);
+        spec.options = options;
+        let pmr_ids = manager.start(spec)?;
+        print_started(&pmr_ids)?;
+        list_processes(false)?;
     } else {
-        eprintln!("错误: 必须指定 --config 或 target");
+        return Err(PmrError::InvalidArgument(t!("start.config_or_target")));
     }
 
     Ok(())
 }
 
-fn start_existing_process(process: &PmrProcessInfo) -> io::Result<()> {
-    if process.status == "running" {
-        println!("进程 '{}' 已经在运行中，PID: {}", process.name, process.pid);
-        return Ok(());
+/// 按依赖关系排序，被依赖的进程先启动
+pub(crate) fn start_order(targets: Vec<PmrProcessInfo>) -> Vec<PmrProcessInfo> {
+    let nodes: Vec<(String, Vec<String>)> = targets
+        .iter()
+        .map(|p| (p.name.clone(), p.depends_on.clone()))
+        .collect();
+    match dependency_order(&nodes) {
+        Ok(order) => order.into_iter().map(|i| targets[i].clone()).collect(),
+        Err(_) => targets,
     }
+}
 
-    // 先停止进程
-    stop_process(&process.pmr_id.to_string(), false);
+/// 根据控制接口 start 请求中的应用生成进程记录和实例数量。
+///
+/// 字段与配置文件中的单个应用相同，name 省略时使用程序名，args 可以省略
+pub(crate) fn process_from_json(
+    mut app: serde_json::Value,
+    namespace: String,
+    workdir: String,
+) -> Result<(PmrProcessInfo, Option<u32>), PmrError> {
+    if let Some(object) = app.as_object_mut() {
+        if !object.contains_key("name") {
+            let name = object
+                .get("program")
+                .and_then(|p| p.as_str())
+                .map(|p| p.split('/').next_back().unwrap_or(p).to_string());
+            if let Some(name) = name {
+                object.insert("name".to_string(), name.into());
+            }
+        }
+        object
+            .entry("args")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+    }
+    let config: Config =
+        serde_json::from_value(app).map_err(|e| PmrError::InvalidArgument(e.to_string()))?;
+    process_from_config(config, namespace, workdir, None, &StartOptions::default())
+}
+
+/// 根据配置文件中的一个应用生成进程记录，命令行参数优先于配置文件
+fn process_from_config(
+    config: Config,
+    namespace: String,
+    workdir: String,
+    source: Option<&str>,
+    options: &StartOptions,
+) -> Result<(PmrProcessInfo, Option<u32>), PmrError> {
+    let mut process =
+        PmrProcessInfo::new(config.name, namespace, workdir, config.program, config.args);
+    process.env = config.env;
+    process.max_memory_restart = match options.max_memory_restart {
+        Some(limit) => Some(limit),
+        None => config
+            .max_memory_restart
+            .as_deref()
+            .map(parse_memory_size)
+            .transpose()
+            .map_err(PmrError::Config)?,
+    };
+    process.cron_restart = match options.cron_restart.clone() {
+        Some(expr) => Some(expr),
+        None => config
+            .cron_restart
+            .map(|expr| validate_cron(&expr))
+            .transpose()
+            .map_err(PmrError::Config)?,
+    };
+    match config.watch {
+        Some(WatchConfig::Enabled(watch)) => process.watch = watch,
+        Some(WatchConfig::Paths(paths)) => {
+            process.watch = true;
+            process.watch_paths = paths;
+        }
+        None => {}
+    }
+    if let Some(ignore_watch) = config.ignore_watch {
+        process.ignore_watch = ignore_watch;
+    }
+    if let Some(delay) = config.watch_delay {
+        process.watch_delay = delay;
+    }
+    process.wait_ready = config
+        .wait_ready
+        .map(|signal| validate_ready_signal(&signal))
+        .transpose()
+        .map_err(PmrError::Config)?;
+    if let Some(timeout) = config.listen_timeout {
+        process.listen_timeout = timeout;
+    }
+    if let Some(check) = config.health_check {
+        let check = validate_health_check(&check).map_err(PmrError::Config)?;
+        let mut health = new_health_check(check);
+        health.expect_status = config.health_status;
+        health.interval = config.health_interval.unwrap_or(health.interval);
+        health.timeout = config.health_timeout.unwrap_or(health.timeout);
+        health.threshold = config.health_threshold.unwrap_or(health.threshold);
+        health.auto_restart = config.health_restart.unwrap_or(false);
+        process.health_check = Some(health);
+    }
+    process.depends_on = config.depends_on;
+    process.hooks = Hooks {
+        pre_start: config.hooks.pre_start.map(Hook::from),
+        post_start: config.hooks.post_start.map(Hook::from),
+        pre_stop: config.hooks.pre_stop.map(Hook::from),
+        post_stop: config.hooks.post_stop.map(Hook::from),
+    };
+    options.apply_watch(&mut process);
+    options.apply_ready(&mut process);
+    options.apply_health(&mut process);
+    process.user = config
+        .user
+        .map(|user| validate_user(&user))
+        .transpose()
+        .map_err(PmrError::Config)?;
+    process.group = config
+        .group
+        .map(|group| validate_group(&group))
+        .transpose()
+        .map_err(PmrError::Config)?;
+    process.groups = config
+        .groups
+        .iter()
+        .map(|group| validate_group(group))
+        .collect::<Result<_, _>>()
+        .map_err(PmrError::Config)?;
+    options.apply_hooks(&mut process);
+    options.apply_user(&mut process);
+    let instances = match options.instances {
+        Some(n) => Some(n),
+        None => match config.instances {
+            Some(InstancesConfig::Count(0)) => Some(parse_instances("max").unwrap_or(1)),
+            Some(InstancesConfig::Count(n)) => Some(n),
+            Some(InstancesConfig::Text(text)) => {
+                Some(parse_instances(&text).map_err(PmrError::Config)?)
+            }
+            None => None,
+        },
+    };
+    process.config_source = source.map(String::from);
+    Ok((process, instances))
+}
+
+/// 按 depends_on 的拓扑顺序启动配置文件中的所有应用，
+/// 被依赖的应用启动后要等到就绪（配置了健康检查时等到检查通过）才启动下一个
+fn start_ecosystem(
+    apps: Vec<Config>,
+    namespace: String,
+    workdir: String,
+    source: &str,
+    options: &StartOptions,
+) -> Result<(), PmrError> {
+    let mut planned = Vec::new();
+    for config in apps {
+        planned.push(process_from_config(
+            config,
+            namespace.clone(),
+            workdir.clone(),
+            Some(source),
+            options,
+        )?);
+    }
 
+    let nodes: Vec<(String, Vec<String>)> = planned
+        .iter()
+        .map(|(p, _)| (p.name.clone(), p.depends_on.clone()))
+        .collect();
+    let order = dependency_order(&nodes).map_err(PmrError::Config)?;
+
+    // 不在配置文件中的依赖必须是已经在运行的进程
+    let existing = DumpConfig::get_instance()
+        .list_processes()
+        .map_err(PmrError::Registry)?;
+    for (process, _) in &planned {
+        for dep in &process.depends_on {
+            let in_file = nodes.iter().any(|(name, _)| name == dep);
+            let running = existing.iter().any(|p| &p.name == dep && p.is_alive());
+            if !in_file && !running {
+                return Err(PmrError::Config(t!(
+                    "start.dependency_not_running",
+                    process.name,
+                    dep
+                )));
+            }
+        }
+    }
+
+    for index in order {
+        let (process, instances) = planned[index].clone();
+        let name = process.name.clone();
+        print_ready_wait(&process);
+        let pmr_ids = launch_instances(process, instances)?;
+        print_started(&pmr_ids)?;
+        if planned.iter().any(|(p, _)| p.depends_on.contains(&name)) {
+            wait_online(&name)?;
+        }
+    }
+
+    list_processes(false)
+}
+
+/// 等待同名的所有实例在线，配置了健康检查时需要检查通过
+fn wait_online(name: &str) -> Result<(), PmrError> {
+    let processes = DumpConfig::get_instance()
+        .list_processes()
+        .map_err(PmrError::Registry)?;
+    for process in processes.iter().filter(|p| p.name == name) {
+        if !process.is_alive() {
+            return Err(PmrError::start_failed(
+                process,
+                t!("start.dependency_exited"),
+            ));
+        }
+        let Some(check) = &process.health_check else {
+            continue;
+        };
+
+        println!("{}", t!("start.waiting_health", name));
+        let credentials =
+            process_credentials(process).map_err(|e| PmrError::start_failed(process, e))?;
+        let deadline = Instant::now() + Duration::from_millis(process.listen_timeout);
+        loop {
+            match run_health_check(check, &process.workdir, process.pid, credentials.as_ref()) {
+                Ok(_) => break,
+                Err(e) if Instant::now() >= deadline => {
+                    return Err(PmrError::start_failed(
+                        process,
+                        t!("start.dependency_unhealthy", process.listen_timeout, e),
+                    ));
+                }
+                Err(_) => {
+                    if !process.is_alive() {
+                        return Err(PmrError::start_failed(
+                            process,
+                            t!("start.dependency_exited"),
+                        ));
+                    }
+                    thread::sleep(Duration::from_millis(200));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 按照进程记录启动程序，标准输出和标准错误追加到该进程的日志文件
+pub(crate) fn spawn_process(
+    process: &PmrProcessInfo,
+    ready: Option<&ReadyWaiter>,
+    credentials: Option<&Credentials>,
+) -> io::Result<Child> {
     // 获取日志文件路径
     let log_path = log::get_log_path(process.pmr_id)?;
 
@@ -199,32 +655,149 @@ fn start_existing_process(process: &PmrProcessInfo) -> io::Result<()> {
     let stdout_log = log_file.try_clone()?;
     let stderr_log = log_file.try_clone()?;
 
-    // 重新启动进程
     let mut cmd = Command::new(&process.program);
+    if let Some(credentials) = credentials {
+        apply_credentials(&mut cmd, credentials)?;
+    }
     cmd.args(&process.args)
+        .envs(&process.env)
         .stdout(Stdio::from(stdout_log))
         .stderr(Stdio::from(stderr_log));
+    // 集群模式下告知每个实例自己的编号
+    if let Some(instance_id) = process.instance_id {
+        cmd.env("PMR_INSTANCE_ID", instance_id.to_string())
+            .env("NODE_APP_INSTANCE", instance_id.to_string());
+    }
+    if let Some(socket) = ready.and_then(|r| r.notify_socket()) {
+        cmd.env("NOTIFY_SOCKET", socket);
+    }
+    if !process.workdir.is_empty() {
+        cmd.current_dir(&process.workdir);
+    }
 
-    match cmd.spawn() {
-        Ok(child) => {
-            let pid = child.id();
-            println!("启动进程 '{}' PID: {}", process.name, pid);
-
-            let dump_config = DumpConfig::get_instance();
-            dump_config
-                .update_process_status(process.pmr_id, pid, "running".to_string())
-                .expect("无法更新进程状态");
+    let child = cmd.spawn()?;
+    track_child(child.id());
+    Ok(child)
+}
 
-            // 显示进程列表
-            list_processes(false);
-            Ok(())
+/// 启动进程并更新状态，返回新进程的PID。
+///
+/// 启动前后分别执行 pre_start 和 post_start 钩子。
+/// 配置了 wait_ready 时先标记为 starting，收到就绪信号后才标记为 running；
+/// 超过 listen_timeout 仍视为已启动，只有进程在就绪前退出才返回错误。
+pub(crate) fn spawn_until_ready(process: &PmrProcessInfo) -> io::Result<u32> {
+    let dump_config = DumpConfig::get_instance();
+    let credentials = process_credentials(process)?;
+    // pre_start 钩子失败时不启动进程
+    run_hook(process, HookStage::PreStart, credentials.as_ref()).map_err(io::Error::other)?;
+
+    let mut waiter = ReadyWaiter::prepare(process, credentials.as_ref())?;
+    let mut child = spawn_process(process, waiter.as_ref(), credentials.as_ref())?;
+    let pid = child.id();
+
+    if let Some(waiter) = waiter.as_mut() {
+        dump_config.update_process_status(process.pmr_id, pid, "starting".to_string())?;
+
+        match waiter.wait(&mut child, Duration::from_millis(process.listen_timeout)) {
+            Ok(()) => {}
+            Err(ReadyError::Timeout) => eprintln!(
+                "{}",
+                t!("ready.timeout", process.name, process.listen_timeout)
+            ),
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                untrack_child(pid);
+                dump_config.update_process_status(process.pmr_id, 0, "stopped".to_string())?;
+                return Err(io::Error::other(e.to_string()));
+            }
         }
-        Err(e) => {
-            eprintln!("启动进程失败: {}", e);
-            Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("启动进程失败: {}", e),
-            ))
+    }
+
+    dump_config.update_process_status(process.pmr_id, pid, "running".to_string())?;
+    if let Err(e) = run_hook(process, HookStage::PostStart, credentials.as_ref()) {
+        eprintln!("{}", t!("hook.process_failed", process.name, e));
+    }
+    Ok(pid)
+}
+
+/// 启动新进程并显示进程列表
+fn start_new_processes(process: PmrProcessInfo, instances: Option<u32>) -> Result<(), PmrError> {
+    print_ready_wait(&process);
+    let pmr_ids = launch_instances(process, instances)?;
+    print_started(&pmr_ids)?;
+    list_processes(false)
+}
+
+/// 配置了 wait_ready 的进程启动前提示需要等待就绪
+pub(crate) fn print_ready_wait(process: &PmrProcessInfo) {
+    if process.wait_ready.is_some() {
+        println!("{}", t!("ready.waiting", process.name));
+    }
+}
+
+/// 打印新启动的进程及其PID
+pub(crate) fn print_started(pmr_ids: &[u32]) -> Result<(), PmrError> {
+    for (_, line) in current_lines(pmr_ids, |p| t!("start.started", p.name, p.pid))? {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// 指定instances时以集群模式启动多个实例，每个实例有自己的pmr_id和日志文件，返回新的 pmr_id
+pub(crate) fn launch_instances(
+    process: PmrProcessInfo,
+    instances: Option<u32>,
+) -> Result<Vec<u32>, PmrError> {
+    match instances {
+        Some(instances) => {
+            let mut ids = Vec::new();
+            for instance_id in 0..instances {
+                let mut instance = process.clone();
+                instance.instance_id = Some(instance_id);
+                ids.push(launch_new_process(instance)?);
+            }
+            Ok(ids)
         }
+        None => Ok(vec![launch_new_process(process)?]),
     }
 }
+
+/// 添加进程记录并启动，返回新的 pmr_id。启动失败时删除刚添加的记录
+pub(crate) fn launch_new_process(mut process: PmrProcessInfo) -> Result<u32, PmrError> {
+    let dump_config = DumpConfig::get_instance();
+    process.pmr_id = dump_config
+        .add_process(process.clone())
+        .map_err(PmrError::Registry)?;
+
+    // 日志路径和状态更新都需要 pmr_id，所以先添加记录再启动
+    let pid = spawn_until_ready(&process).map_err(|e| {
+        let _ = dump_config.delete_process(process.pmr_id);
+        PmrError::start_failed(&process, e)
+    })?;
+    emit(Event::new(EventKind::Start, &process, "").with_pid(pid));
+    Ok(process.pmr_id)
+}
+
+/// 进程已经在运行，start 时保持不变
+pub(crate) fn is_already_running(process: &PmrProcessInfo) -> bool {
+    process.status == "running" && process.is_alive()
+}
+
+/// 启动已存在的进程，已经在运行时不做任何操作
+pub(crate) fn start_existing_process(process: &PmrProcessInfo) -> io::Result<()> {
+    if is_already_running(process) {
+        return Ok(());
+    }
+    if is_crashed(process) {
+        record_crash(process, None, None)?;
+    }
+
+    // 先停止进程
+    stop_existing_process(process).map_err(io::Error::other)?;
+
+    // 重新启动进程
+    let pid = spawn_until_ready(process)?;
+    emit(Event::new(EventKind::Start, process, "").with_pid(pid));
+    Ok(())
+}
@@ -1,153 +1,184 @@
+use super::super::base::dependency::dependency_order;
+use super::super::base::event::{emit, Event, EventKind};
+use super::super::base::hook::{run_hook, HookStage};
+use super::super::base::process::PmrProcessInfo;
+use super::super::base::user::{process_credentials, Credentials};
 use super::super::config::dump::DumpConfig;
+use super::super::error::PmrError;
+use super::super::manager::ProcessManager;
 use super::list::list_processes;
+use super::target::print_batch;
 use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub fn stop_process(target: &str, show_list: bool) {
+// 发送SIGTERM后等待进程退出的时间，超时后强制结束
+const KILL_TIMEOUT: Duration = Duration::from_millis(1600);
+
+pub fn stop_process(target: &str, show_list: bool) -> Result<(), PmrError> {
+    let manager = ProcessManager::new()?;
+
+    // target可以是pmr_id、name、namespace或all
+    let lines: Vec<(u32, String)> = stop_order(manager.find(target)?)
+        .iter()
+        .map(|p| (p.pmr_id, stop_message(p)))
+        .collect();
+    let result = manager.stop(target);
+    print_batch(&t!("stop.action"), &lines, &result);
+
+    // 根据show_list参数决定是否显示进程列表
+    if show_list {
+        println!("\n{}", t!("list.current"));
+        list_processes(false)?;
+    }
+    result.map(|_| ())
+}
+
+/// 停止成功后显示的消息，需要在停止之前根据进程的状态生成
+fn stop_message(process: &PmrProcessInfo) -> String {
+    if process.pid == 0 {
+        t!("stop.already_stopped", process.name)
+    } else if !process.is_alive() {
+        t!("stop.not_running", process.name, process.pid)
+    } else {
+        t!("stop.stopped", process.name, process.pid)
+    }
+}
+
+/// 按依赖关系的逆序排列，依赖其他应用的进程先停止
+pub(crate) fn stop_order(targets: Vec<PmrProcessInfo>) -> Vec<PmrProcessInfo> {
+    let nodes: Vec<(String, Vec<String>)> = targets
+        .iter()
+        .map(|p| (p.name.clone(), p.depends_on.clone()))
+        .collect();
+    match dependency_order(&nodes) {
+        Ok(order) => order
+            .into_iter()
+            .rev()
+            .map(|i| targets[i].clone())
+            .collect(),
+        Err(_) => targets,
+    }
+}
+
+/// 用户主动停止进程，进程原本在运行时发出 stop 事件
+pub(crate) fn stop_and_notify(process: &PmrProcessInfo, reason: &str) -> Result<(), String> {
+    let running = process.is_alive();
+    stop_existing_process(process)?;
+    if running {
+        emit(Event::new(EventKind::Stop, process, reason));
+    }
+    Ok(())
+}
+
+/// 立即结束进程，运行中的进程在结束前后执行 pre_stop 和 post_stop 钩子
+pub(crate) fn stop_existing_process(process: &PmrProcessInfo) -> Result<(), String> {
+    let running = process.is_alive();
+    let credentials = stop_hook_credentials(process, running);
+    if running {
+        run_stop_hook(process, HookStage::PreStop, &credentials);
+    }
+    let result = kill_process(process);
+    if running && result.is_ok() {
+        run_stop_hook(process, HookStage::PostStop, &credentials);
+    }
+    result
+}
+
+/// 运行中的进程配置了停止钩子时，解析钩子使用的用户和组
+fn stop_hook_credentials(
+    process: &PmrProcessInfo,
+    running: bool,
+) -> Result<Option<Credentials>, String> {
+    let hooks = &process.hooks;
+    if !running || (hooks.pre_stop.is_none() && hooks.post_stop.is_none()) {
+        return Ok(None);
+    }
+    process_credentials(process).map_err(|e| e.to_string())
+}
+
+/// 停止钩子失败不会阻止停止进程，无法解析用户和组时不执行钩子
+fn run_stop_hook(
+    process: &PmrProcessInfo,
+    stage: HookStage,
+    credentials: &Result<Option<Credentials>, String>,
+) {
+    let result = match credentials {
+        Ok(credentials) => run_hook(process, stage, credentials.as_ref()),
+        Err(e) => Err(t!("hook.failed", stage.name(), e)),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", t!("hook.process_failed", process.name, e));
+    }
+}
+
+fn kill_process(process: &PmrProcessInfo) -> Result<(), String> {
     let dump_config = DumpConfig::get_instance();
-    match dump_config.list_processes() {
-        Ok(processes) => {
-            let mut found = false;
-
-            // 首先尝试将target解析为pmr_id
-            if let Ok(pmr_id) = target.parse::<u32>() {
-                if let Some(process) = processes.iter().find(|p| p.pmr_id == pmr_id) {
-                    if process.pid > 0 {
-                        // 检查进程是否真实在运行
-                        let is_running = if cfg!(target_os = "windows") {
-                            Command::new("tasklist")
-                                .args(&["/FI", &format!("PID eq {}", process.pid)])
-                                .output()
-                                .map(|output| {
-                                    String::from_utf8_lossy(&output.stdout)
-                                        .contains(&process.pid.to_string())
-                                })
-                                .unwrap_or(false)
-                        } else {
-                            Command::new("ps")
-                                .args(&["-p", &process.pid.to_string()])
-                                .output()
-                                .map(|output| output.status.success())
-                                .unwrap_or(false)
-                        };
-
-                        if !is_running {
-                            println!("进程 '{}' (PID: {}) 未在运行", process.name, process.pid);
-                            dump_config
-                                .update_process_status(process.pmr_id, 0, "stopped".to_string())
-                                .expect("无法更新进程状态");
-                            found = true;
-                        }
-
-                        // 根据操作系统使用不同的命令终止进程
-                        let output = if cfg!(target_os = "windows") {
-                            Command::new("taskkill")
-                                .args(&["/PID", &process.pid.to_string(), "/F"])
-                                .output()
-                        } else {
-                            Command::new("kill")
-                                .args(&["-9", &process.pid.to_string()])
-                                .output()
-                        }
-                        .expect("无法执行进程终止命令");
-
-                        if output.status.success() {
-                            println!("已停止进程 '{}' (PID: {})", process.name, process.pid);
-                            dump_config
-                                .update_process_status(process.pmr_id, 0, "stopped".to_string())
-                                .expect("无法更新进程状态");
-                            found = true;
-                        } else {
-                            eprintln!(
-                                "停止进程失败 '{}' (PID: {}): {}",
-                                process.name,
-                                process.pid,
-                                String::from_utf8_lossy(&output.stderr)
-                            );
-                        }
-                    } else {
-                        println!("进程 '{}' 已经停止", process.name);
-                        dump_config
-                            .update_process_status(process.pmr_id, 0, "stopped".to_string())
-                            .expect("无法更新进程状态");
-                        found = true;
-                    }
-                }
-            }
 
-            // 如果不是pmr_id，尝试按name查找
-            if !found {
-                if let Some(process) = processes.iter().find(|p| p.name == target) {
-                    if process.pid > 0 {
-                        // 检查进程是否真实在运行
-                        let is_running = if cfg!(target_os = "windows") {
-                            Command::new("tasklist")
-                                .args(&["/FI", &format!("PID eq {}", process.pid)])
-                                .output()
-                                .map(|output| {
-                                    String::from_utf8_lossy(&output.stdout)
-                                        .contains(&process.pid.to_string())
-                                })
-                                .unwrap_or(false)
-                        } else {
-                            Command::new("ps")
-                                .args(&["-p", &process.pid.to_string()])
-                                .output()
-                                .map(|output| output.status.success())
-                                .unwrap_or(false)
-                        };
-
-                        if !is_running {
-                            println!("进程 '{}' (PID: {}) 未在运行", process.name, process.pid);
-                            dump_config
-                                .update_process_status(process.pmr_id, 0, "stopped".to_string())
-                                .expect("无法更新进程状态");
-                            return;
-                        }
-
-                        // 根据操作系统使用不同的命令终止进程
-                        let output = if cfg!(target_os = "windows") {
-                            Command::new("taskkill")
-                                .args(&["/PID", &process.pid.to_string(), "/F"])
-                                .output()
-                        } else {
-                            Command::new("kill")
-                                .args(&["-9", &process.pid.to_string()])
-                                .output()
-                        }
-                        .expect("无法执行进程终止命令");
-
-                        if output.status.success() {
-                            println!("已停止进程 '{}' (PID: {})", process.name, process.pid);
-                            dump_config
-                                .update_process_status(process.pmr_id, 0, "stopped".to_string())
-                                .expect("无法更新进程状态");
-                        } else {
-                            eprintln!(
-                                "停止进程失败 '{}' (PID: {}): {}",
-                                process.name,
-                                process.pid,
-                                String::from_utf8_lossy(&output.stderr)
-                            );
-                        }
-                    } else {
-                        println!("进程 '{}' 已经停止", process.name);
-                        dump_config
-                            .update_process_status(process.pmr_id, 0, "stopped".to_string())
-                            .expect("无法更新进程状态");
-                    }
-                } else {
-                    eprintln!("未找到进程: {}", target);
-                }
-            }
+    if process.pid == 0 {
+        dump_config
+            .update_process_status(process.pmr_id, 0, "stopped".to_string())
+            .map_err(|e| t!("process.update_status_failed", e))?;
+        return Ok(());
+    }
 
-            // 根据show_list参数决定是否显示进程列表
-            if show_list {
-                println!("\n当前进程列表:");
-                list_processes(false);
-            }
+    // 检查PID是否仍属于该进程，PID可能在进程退出或重启系统后被其他进程复用
+    if !process.is_alive() {
+        dump_config
+            .update_process_status(process.pmr_id, 0, "stopped".to_string())
+            .map_err(|e| t!("process.update_status_failed", e))?;
+        return Ok(());
+    }
+
+    // 根据操作系统使用不同的命令终止进程
+    let output = if cfg!(target_os = "windows") {
+        Command::new("taskkill")
+            .args(["/PID", &process.pid.to_string(), "/F"])
+            .output()
+    } else {
+        Command::new("kill")
+            .args(["-9", &process.pid.to_string()])
+            .output()
+    }
+    .map_err(|e| t!("stop.kill_command_failed", e))?;
+
+    if output.status.success() {
+        dump_config
+            .update_process_status(process.pmr_id, 0, "stopped".to_string())
+            .map_err(|e| t!("process.update_status_failed", e))?;
+    } else {
+        let error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(t!("stop.kill_failed", process.pid, error));
+    }
+    Ok(())
+}
+
+/// 先请求进程退出（Unix下为SIGTERM），超时后再强制结束
+pub(crate) fn stop_gracefully(process: &PmrProcessInfo) -> Result<(), String> {
+    let running = process.is_alive();
+    let credentials = stop_hook_credentials(process, running);
+    if running {
+        run_stop_hook(process, HookStage::PreStop, &credentials);
+        let pid = process.pid.to_string();
+        let requested = if cfg!(target_os = "windows") {
+            Command::new("taskkill").args(["/PID", &pid]).output()
+        } else {
+            Command::new("kill").args(["-TERM", &pid]).output()
         }
-        Err(e) => {
-            eprintln!("读取进程列表失败: {}", e);
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+        if requested {
+            let deadline = Instant::now() + KILL_TIMEOUT;
+            while process.is_alive() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(100));
+            }
         }
     }
+
+    let result = kill_process(process);
+    if running && result.is_ok() {
+        run_stop_hook(process, HookStage::PostStop, &credentials);
+    }
+    result
 }
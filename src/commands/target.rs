@@ -0,0 +1,151 @@
+use super::super::base::process::PmrProcessInfo;
+use super::super::config::dump::DumpConfig;
+use super::super::error::PmrError;
+
+/// 将命令行目标解析为进程列表。
+///
+/// 依次尝试：`all`（所有进程）、pmr_id、进程名称、命名空间。
+pub fn resolve_target(processes: &[PmrProcessInfo], target: &str) -> Vec<PmrProcessInfo> {
+    if target == "all" {
+        return processes.to_vec();
+    }
+
+    if let Ok(pmr_id) = target.parse::<u32>() {
+        if let Some(process) = processes.iter().find(|p| p.pmr_id == pmr_id) {
+            return vec![process.clone()];
+        }
+    }
+
+    let by_name: Vec<PmrProcessInfo> = processes
+        .iter()
+        .filter(|p| p.name == target)
+        .cloned()
+        .collect();
+    if !by_name.is_empty() {
+        return by_name;
+    }
+
+    processes
+        .iter()
+        .filter(|p| p.namespace == target)
+        .cloned()
+        .collect()
+}
+
+/// 批量操作的执行结果
+#[derive(Default)]
+pub struct BatchResult {
+    pub succeeded: Vec<u32>, // 成功的 pmr_id
+    pub failed: Vec<PmrError>,
+}
+
+impl BatchResult {
+    pub fn record(&mut self, process: &PmrProcessInfo, result: Result<(), PmrError>) {
+        match result {
+            Ok(()) => self.succeeded.push(process.pmr_id),
+            Err(e) => self.failed.push(e),
+        }
+    }
+
+    /// 全部成功时返回成功的 pmr_id；只有一个进程时直接返回它的错误，否则返回 Batch
+    pub fn into_result(mut self) -> Result<Vec<u32>, PmrError> {
+        match (self.succeeded.len(), self.failed.len()) {
+            (_, 0) => Ok(self.succeeded),
+            (0, 1) => Err(self.failed.remove(0)),
+            _ => Err(PmrError::Batch {
+                succeeded: self.succeeded,
+                failed: self.failed,
+            }),
+        }
+    }
+}
+
+/// 按操作之后的进程记录生成每个进程的消息，例如显示重启后的新PID
+pub(crate) fn current_lines(
+    pmr_ids: &[u32],
+    message: impl Fn(&PmrProcessInfo) -> String,
+) -> Result<Vec<(u32, String)>, PmrError> {
+    let processes = DumpConfig::get_instance()
+        .list_processes()
+        .map_err(PmrError::Registry)?;
+    Ok(pmr_ids
+        .iter()
+        .filter_map(|pmr_id| processes.iter().find(|p| p.pmr_id == *pmr_id))
+        .map(|p| (p.pmr_id, message(p)))
+        .collect())
+}
+
+/// 打印批量操作中成功的进程对应的消息，多个进程时再打印成功和失败的汇总。
+///
+/// lines 为每个目标进程的 pmr_id 和操作成功时显示的消息
+pub(crate) fn print_batch(
+    action: &str,
+    lines: &[(u32, String)],
+    result: &Result<Vec<u32>, PmrError>,
+) {
+    let (succeeded, failed): (&[u32], &[PmrError]) = match result {
+        Ok(succeeded) => (succeeded, &[]),
+        Err(PmrError::Batch { succeeded, failed }) => (succeeded, failed),
+        Err(_) => (&[], &[]),
+    };
+    for (pmr_id, line) in lines {
+        if succeeded.contains(pmr_id) {
+            println!("{}", line);
+        }
+    }
+    if lines.len() <= 1 {
+        return;
+    }
+    println!(
+        "\n{}: {}",
+        action,
+        t!(
+            "batch.counts",
+            succeeded.len(),
+            lines.len().saturating_sub(succeeded.len())
+        )
+    );
+    for error in failed {
+        eprintln!("  {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pmr_id: u32, name: &str, namespace: &str) -> PmrProcessInfo {
+        let mut process = PmrProcessInfo::new(
+            name.to_string(),
+            namespace.to_string(),
+            "/".to_string(),
+            "sleep".to_string(),
+            Vec::new(),
+        );
+        process.pmr_id = pmr_id;
+        process
+    }
+
+    fn ids(processes: Vec<PmrProcessInfo>) -> Vec<u32> {
+        processes.iter().map(|p| p.pmr_id).collect()
+    }
+
+    #[test]
+    fn targets() {
+        let processes = vec![
+            process(1, "web", "default"),
+            process(2, "web", "default"),
+            process(3, "worker", "jobs"),
+            process(4, "2", "jobs"),
+        ];
+        assert_eq!(ids(resolve_target(&processes, "all")), vec![1, 2, 3, 4]);
+        assert_eq!(ids(resolve_target(&processes, "3")), vec![3]);
+        // pmr_id 优先于同名的进程
+        assert_eq!(ids(resolve_target(&processes, "2")), vec![2]);
+        assert_eq!(ids(resolve_target(&processes, "web")), vec![1, 2]);
+        assert_eq!(ids(resolve_target(&processes, "jobs")), vec![3, 4]);
+        // 不存在的 pmr_id 按名称查找
+        assert_eq!(ids(resolve_target(&processes[3..], "2")), vec![4]);
+        assert!(resolve_target(&processes, "missing").is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+use super::super::base::process::PmrProcessInfo;
+use super::super::config::dump::DumpConfig;
+use super::super::error::PmrError;
+use super::restart::restart_and_report;
+use super::target::resolve_target;
+use glob::Pattern;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+/// 开启或关闭进程的文件监听，由 pmr daemon 负责实际的监听和重启
+pub fn set_watch(target: &str, enabled: bool) -> Result<(), PmrError> {
+    let dump_config = DumpConfig::get_instance();
+    let processes = dump_config.list_processes().map_err(PmrError::Registry)?;
+
+    let targets = resolve_target(&processes, target);
+    if targets.is_empty() {
+        return Err(PmrError::NotFound(target.to_string()));
+    }
+
+    for process in &targets {
+        dump_config
+            .set_watch(process.pmr_id, enabled)
+            .map_err(PmrError::Registry)?;
+        if enabled {
+            println!("{}", t!("watch.enabled", process.name));
+        } else {
+            println!("{}", t!("watch.disabled", process.name));
+        }
+    }
+    Ok(())
+}
+
+/// 单个进程的监听状态
+struct AppWatcher {
+    // 监听配置，变化时需要重新创建监听
+    signature: (String, Vec<String>),
+    ignore: Vec<Pattern>,
+    root: PathBuf,
+    delay: Duration,
+    pending: Option<Instant>,
+    _watcher: RecommendedWatcher,
+}
+
+/// 管理所有开启了 watch 的进程，在文件变化并经过防抖延迟后重启进程
+pub struct FileWatcher {
+    apps: HashMap<u32, AppWatcher>,
+    // ~/.pmr，日志和dump.json的变化不能触发重启，否则在家目录中启动的进程会不停重启
+    data_dirs: Vec<PathBuf>, // 原路径和规范化后的路径
+    sender: Sender<(u32, Vec<PathBuf>)>,
+    receiver: Receiver<(u32, Vec<PathBuf>)>,
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut data_dirs: Vec<PathBuf> = dirs::home_dir()
+            .map(|home| home.join(".pmr"))
+            .into_iter()
+            .collect();
+        if let Some(canonical) = data_dirs.first().and_then(|d| d.canonicalize().ok()) {
+            if !data_dirs.contains(&canonical) {
+                data_dirs.push(canonical);
+            }
+        }
+        Self {
+            apps: HashMap::new(),
+            data_dirs,
+            sender,
+            receiver,
+        }
+    }
+
+    /// 根据最新的进程列表创建或移除监听
+    pub fn sync(&mut self, processes: &[PmrProcessInfo]) {
+        self.apps.retain(|id, _| {
+            processes
+                .iter()
+                .any(|p| p.pmr_id == *id && p.watch && p.status == "running")
+        });
+
+        for process in processes {
+            if !process.watch || process.status != "running" {
+                continue;
+            }
+            let signature = (process.workdir.clone(), process.watch_paths.clone());
+            if let Some(app) = self.apps.get_mut(&process.pmr_id) {
+                if app.signature == signature {
+                    // 忽略规则和延迟不需要重新创建监听
+                    app.ignore = compile_patterns(&process.ignore_watch);
+                    app.delay = Duration::from_millis(process.watch_delay);
+                    continue;
+                }
+            }
+
+            match self.watch_process(process, signature) {
+                Ok(app) => {
+                    self.apps.insert(process.pmr_id, app);
+                }
+                Err(e) => eprintln!("{}", t!("watch.watch_failed", process.name, e)),
+            }
+        }
+    }
+
+    fn watch_process(
+        &self,
+        process: &PmrProcessInfo,
+        signature: (String, Vec<String>),
+    ) -> notify::Result<AppWatcher> {
+        let pmr_id = process.pmr_id;
+        let sender = self.sender.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    let _ = sender.send((pmr_id, event.paths));
+                }
+            }
+        })?;
+
+        let root = PathBuf::from(&process.workdir);
+        let paths: Vec<PathBuf> = if process.watch_paths.is_empty() {
+            vec![root.clone()]
+        } else {
+            process.watch_paths.iter().map(|p| root.join(p)).collect()
+        };
+        for path in &paths {
+            watcher.watch(path, RecursiveMode::Recursive)?;
+        }
+        let watched = paths
+            .iter()
+            .map(|p| p.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("{}", t!("watch.watching", process.name, watched));
+
+        Ok(AppWatcher {
+            signature,
+            ignore: compile_patterns(&process.ignore_watch),
+            root,
+            delay: Duration::from_millis(process.watch_delay),
+            pending: None,
+            _watcher: watcher,
+        })
+    }
+
+    /// 处理文件变化事件，防抖延迟结束后重启进程
+    pub fn poll(&mut self) {
+        while let Ok((pmr_id, paths)) = self.receiver.try_recv() {
+            if let Some(app) = self.apps.get_mut(&pmr_id) {
+                if paths.iter().any(|p| {
+                    !is_inside(&self.data_dirs, p) && !is_ignored(&app.root, &app.ignore, p)
+                }) {
+                    // 每次变化都会推迟重启时间，连续的变化只触发一次重启
+                    app.pending = Some(Instant::now() + app.delay);
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let due: Vec<u32> = self
+            .apps
+            .iter_mut()
+            .filter(|(_, app)| app.pending.is_some_and(|deadline| now >= deadline))
+            .map(|(pmr_id, app)| {
+                app.pending = None;
+                *pmr_id
+            })
+            .collect();
+        if due.is_empty() {
+            return;
+        }
+
+        // 重启前读取最新的进程状态，避免使用过期的PID
+        let dump_config = DumpConfig::get_instance();
+        let processes = match dump_config
+            .reload()
+            .and_then(|_| dump_config.list_processes())
+        {
+            Ok(processes) => processes,
+            Err(e) => {
+                eprintln!("{}", t!("list.read_failed", e));
+                return;
+            }
+        };
+        for pmr_id in due {
+            if let Some(process) = processes.iter().find(|p| p.pmr_id == pmr_id) {
+                println!("{}", t!("watch.changed", process.name));
+                restart_and_report(process, "watch");
+            }
+        }
+    }
+}
+
+fn compile_patterns(globs: &[String]) -> Vec<Pattern> {
+    globs
+        .iter()
+        .filter_map(|g| match Pattern::new(g) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                eprintln!("{}", t!("watch.invalid_ignore", g, e));
+                None
+            }
+        })
+        .collect()
+}
+
+/// path 是否在 dirs 中的某个目录下，路径本身或解析符号链接后的路径在其中都算
+fn is_inside(dirs: &[PathBuf], path: &Path) -> bool {
+    dirs.iter().any(|dir| path.starts_with(dir))
+        || path
+            .canonicalize()
+            .is_ok_and(|p| dirs.iter().any(|dir| p.starts_with(dir)))
+}
+
+/// 路径相对于workdir的部分或其中任意一级目录匹配忽略规则时忽略该变化
+fn is_ignored(root: &Path, ignore: &[Pattern], path: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    ignore.iter().any(|pattern| {
+        pattern.matches_path(relative)
+            || relative
+                .components()
+                .any(|c| pattern.matches(&c.as_os_str().to_string_lossy()))
+    })
+}
@@ -1,8 +1,15 @@
-use super::super::base::process::PmrProcessInfo;
+use super::super::base::health::HealthState;
+use super::super::base::process::{
+    default_ignore_watch, migrate_start_time, process_start_time, PmrProcessInfo,
+    DEFAULT_WATCH_DELAY,
+};
+use super::super::base::ready::DEFAULT_LISTEN_TIMEOUT;
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use std::{fs, io, path::PathBuf};
+use std::{fs, io};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct DumpData {
@@ -11,6 +18,7 @@ pub(crate) struct DumpData {
 
 pub struct DumpConfig {
     path: PathBuf,
+    lock_path: PathBuf, // 修改dump.json时持有的锁文件
     data: Mutex<DumpData>,
 }
 
@@ -19,7 +27,7 @@ static INSTANCE: OnceCell<DumpConfig> = OnceCell::new();
 impl DumpConfig {
     fn new() -> io::Result<Self> {
         let home_dir = dirs::home_dir()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Home directory not found"))?;
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, t!("home.not_found")))?;
 
         let config_dir = home_dir.join(".pmr");
         if !config_dir.exists() {
@@ -27,97 +35,183 @@ impl DumpConfig {
         }
 
         let dump_file = config_dir.join("dump.json");
+        let lock_file = config_dir.join("dump.lock");
         let data = if dump_file.exists() {
-            let file_contents = fs::read_to_string(&dump_file)?;
-            // 使用 serde_json::Value 先解析JSON
-            let json: serde_json::Value = serde_json::from_str(&file_contents)?;
-
-            // 手动构建进程列表
-            let processes =
-                if let Some(processes) = json.get("processes").and_then(|v| v.as_array()) {
-                    processes
-                        .iter()
-                        .map(|p| PmrProcessInfo {
-                            pmr_id: p["pmr_id"].as_u64().unwrap_or(0) as u32,
-                            pid: p["pid"].as_u64().unwrap_or(0) as u32,
-                            name: p["name"].as_str().unwrap_or("").to_string(),
-                            namespace: p["namespace"].as_str().unwrap_or("").to_string(),
-                            status: p["status"].as_str().unwrap_or("").to_string(),
-                            program: p["program"].as_str().unwrap_or("").to_string(),
-                            workdir: p["workdir"].as_str().unwrap_or("").to_string(),
-                            args: p["args"]
-                                .as_array()
-                                .map(|a| {
-                                    a.iter()
-                                        .filter_map(|v| v.as_str())
-                                        .map(String::from)
-                                        .collect()
-                                })
-                                .unwrap_or_default(),
-                            restarts: p["restarts"].as_u64().unwrap_or(0) as u32,
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
-                };
-
-            DumpData { processes }
+            Self::load_data(&dump_file)?
         } else {
+            let _lock = FileLock::acquire(&lock_file)?;
             let initial_data = DumpData {
                 processes: Vec::new(),
             };
-            fs::write(&dump_file, serde_json::to_string_pretty(&initial_data)?)?;
-            initial_data
+            // 其他pmr进程可能在加锁前已经创建了dump.json
+            if dump_file.exists() {
+                Self::load_data(&dump_file)?
+            } else {
+                Self::save_data(&dump_file, &initial_data)?;
+                initial_data
+            }
         };
 
         Ok(Self {
             path: dump_file,
+            lock_path: lock_file,
             data: Mutex::new(data),
         })
     }
 
+    fn load_data(dump_file: &Path) -> io::Result<DumpData> {
+        let file_contents = fs::read_to_string(dump_file)?;
+        // 使用 serde_json::Value 先解析JSON
+        let json: serde_json::Value = serde_json::from_str(&file_contents)?;
+
+        // 手动构建进程列表
+        let processes = if let Some(processes) = json.get("processes").and_then(|v| v.as_array()) {
+            processes
+                .iter()
+                .map(|p| PmrProcessInfo {
+                    pmr_id: p["pmr_id"].as_u64().unwrap_or(0) as u32,
+                    pid: p["pid"].as_u64().unwrap_or(0) as u32,
+                    name: p["name"].as_str().unwrap_or("").to_string(),
+                    namespace: p["namespace"].as_str().unwrap_or("").to_string(),
+                    status: p["status"].as_str().unwrap_or("").to_string(),
+                    program: p["program"].as_str().unwrap_or("").to_string(),
+                    workdir: p["workdir"].as_str().unwrap_or("").to_string(),
+                    args: p["args"]
+                        .as_array()
+                        .map(|a| {
+                            a.iter()
+                                .filter_map(|v| v.as_str())
+                                .map(String::from)
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    restarts: p["restarts"].as_u64().unwrap_or(0) as u32,
+                    start_time: p["start_time"].as_u64().unwrap_or(0),
+                    env: p["env"]
+                        .as_object()
+                        .map(|o| {
+                            o.iter()
+                                .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    config_source: p["config_source"].as_str().map(String::from),
+                    status_history: serde_json::from_value(p["status_history"].clone())
+                        .unwrap_or_default(),
+                    last_exit_code: p["last_exit_code"].as_i64().map(|c| c as i32),
+                    max_memory_restart: p["max_memory_restart"].as_u64(),
+                    restart_history: serde_json::from_value(p["restart_history"].clone())
+                        .unwrap_or_default(),
+                    cron_restart: p["cron_restart"].as_str().map(String::from),
+                    watch: p["watch"].as_bool().unwrap_or(false),
+                    watch_paths: serde_json::from_value(p["watch_paths"].clone())
+                        .unwrap_or_default(),
+                    ignore_watch: serde_json::from_value(p["ignore_watch"].clone())
+                        .unwrap_or_else(|_| default_ignore_watch()),
+                    watch_delay: p["watch_delay"].as_u64().unwrap_or(DEFAULT_WATCH_DELAY),
+                    instance_id: p["instance_id"].as_u64().map(|i| i as u32),
+                    wait_ready: p["wait_ready"].as_str().map(String::from),
+                    listen_timeout: p["listen_timeout"]
+                        .as_u64()
+                        .unwrap_or(DEFAULT_LISTEN_TIMEOUT),
+                    health_check: serde_json::from_value(p["health_check"].clone()).ok(),
+                    health: serde_json::from_value(p["health"].clone()).ok(),
+                    depends_on: serde_json::from_value(p["depends_on"].clone()).unwrap_or_default(),
+                    hooks: serde_json::from_value(p["hooks"].clone()).unwrap_or_default(),
+                    user: p["user"].as_str().map(String::from),
+                    group: p["group"].as_str().map(String::from),
+                    groups: serde_json::from_value(p["groups"].clone()).unwrap_or_default(),
+                })
+                .map(|mut process| {
+                    // 旧版本没有记录启动时间，命令行一致时补上，否则视为已停止
+                    if process.start_time == 0 && process.pid > 0 {
+                        process.start_time =
+                            migrate_start_time(process.pid, &process.program, &process.args);
+                    }
+                    process
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(DumpData { processes })
+    }
+
+    /// 重新从磁盘读取dump.json，长时间运行的命令需要读取其他pmr进程写入的最新状态
+    pub fn reload(&self) -> io::Result<()> {
+        let data = Self::load_data(&self.path)?;
+        *self.data.lock().unwrap() = data;
+        Ok(())
+    }
+
     pub fn get_instance() -> &'static DumpConfig {
-        INSTANCE.get_or_init(|| Self::new().expect("Failed to initialize DumpConfig"))
+        Self::try_get_instance().expect("Failed to initialize DumpConfig")
     }
 
-    fn save_data(&self, data: &DumpData) -> io::Result<()> {
+    /// 与 get_instance 相同，但初始化失败时返回错误而不是panic
+    pub fn try_get_instance() -> io::Result<&'static DumpConfig> {
+        INSTANCE.get_or_try_init(Self::new)
+    }
+
+    /// 写入临时文件后重命名，读取方不会看到写了一半的dump.json
+    fn save_data(path: &Path, data: &DumpData) -> io::Result<()> {
         let json = serde_json::to_string_pretty(data)?;
-        fs::write(&self.path, json)
-    }
-
-    pub fn add_process(
-        &self,
-        name: String,
-        namespace: String,
-        workdir: String,
-        program: String,
-        pid: u32,
-        status: String,
-        args: Vec<String>,
-    ) -> io::Result<u32> {
-        let mut data = self.data.lock().unwrap();
-        let new_id = data.processes.iter().map(|p| p.pmr_id).max().unwrap_or(0) + 1;
-
-        data.processes.push(PmrProcessInfo {
-            pmr_id: new_id,
-            name,
-            namespace,
-            pid,
-            status,
-            workdir,
-            program,
-            args,
-            restarts: 0, // 初始化重启次数为0
-        });
-
-        self.save_data(&data)?;
-        Ok(new_id)
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// 在文件锁内重新读取dump.json、修改并写回，同时更新内存中的副本。
+    ///
+    /// 命令行、daemon 和控制接口会同时修改进程列表，直接写回内存中的副本会覆盖
+    /// 其他进程在此期间添加或删除的记录
+    fn modify<T>(&self, f: impl FnOnce(&mut DumpData) -> T) -> io::Result<T> {
+        let mut cache = self.data.lock().unwrap();
+        let _lock = FileLock::acquire(&self.lock_path)?;
+        let mut data = if self.path.exists() {
+            Self::load_data(&self.path)?
+        } else {
+            DumpData {
+                processes: Vec::new(),
+            }
+        };
+        let result = f(&mut data);
+        Self::save_data(&self.path, &data)?;
+        *cache = data;
+        Ok(result)
+    }
+
+    /// 修改 pmr_id 对应的进程记录，记录不存在时不做任何修改
+    fn modify_process(&self, pmr_id: u32, f: impl FnOnce(&mut PmrProcessInfo)) -> io::Result<()> {
+        self.modify(|data| {
+            if let Some(process) = data.processes.iter_mut().find(|p| p.pmr_id == pmr_id) {
+                f(process);
+            }
+        })
+    }
+
+    /// 添加新进程记录，分配并返回新的 pmr_id
+    pub fn add_process(&self, mut process: PmrProcessInfo) -> io::Result<u32> {
+        process.start_time = if process.pid > 0 {
+            process_start_time(process.pid).unwrap_or(0)
+        } else {
+            0
+        };
+        let status = process.status.clone();
+        process.record_status(&status);
+        self.modify(|data| {
+            let new_id = data.processes.iter().map(|p| p.pmr_id).max().unwrap_or(0) + 1;
+            process.pmr_id = new_id;
+            data.processes.push(process);
+            new_id
+        })
     }
 
     pub fn delete_process(&self, id: u32) -> io::Result<()> {
-        let mut data = self.data.lock().unwrap();
-        data.processes.retain(|p| p.pmr_id != id);
-        self.save_data(&data)
+        self.modify(|data| data.processes.retain(|p| p.pmr_id != id))
     }
 
     pub fn list_processes(&self) -> io::Result<Vec<PmrProcessInfo>> {
@@ -126,23 +220,83 @@ impl DumpConfig {
     }
 
     pub fn update_process_status(&self, pmr_id: u32, pid: u32, status: String) -> io::Result<()> {
-        let mut data = self.data.lock().unwrap();
-        if let Some(process) = data.processes.iter_mut().find(|p| p.pmr_id == pmr_id) {
-            process.pid = pid;
-            process.status = status;
-            self.save_data(&data)
+        // 记录启动时间，之后检查存活或发送信号前需要同时比对PID和启动时间
+        let start_time = if pid > 0 {
+            process_start_time(pid).unwrap_or(0)
         } else {
-            Ok(())
-        }
+            0
+        };
+        self.modify_process(pmr_id, |process| {
+            // 新进程需要重新进行健康检查
+            if process.pid != pid {
+                process.health = None;
+            }
+            process.pid = pid;
+            process.record_status(&status);
+            process.start_time = start_time;
+        })
     }
 
-    pub fn increment_restarts(&self, pmr_id: u32) -> io::Result<()> {
-        let mut data = self.data.lock().unwrap();
-        if let Some(process) = data.processes.iter_mut().find(|p| p.pmr_id == pmr_id) {
-            process.restarts = process.restarts.saturating_add(1);
-            self.save_data(&data)
-        } else {
-            Ok(())
+    /// 增加重启次数，并在重启历史中记录原因
+    pub fn increment_restarts(&self, pmr_id: u32, reason: &str) -> io::Result<()> {
+        self.modify_process(pmr_id, |process| process.record_restart(reason))
+    }
+
+    /// 记录进程意外退出，只有记录的PID仍是 pid 时才更新，避免覆盖已重启的进程。
+    /// 返回是否更新了记录，其他pmr命令已经记录了这次退出时返回 false
+    pub fn record_exit(&self, pmr_id: u32, pid: u32, exit_code: Option<i32>) -> io::Result<bool> {
+        self.modify(|data| {
+            let Some(process) = data
+                .processes
+                .iter_mut()
+                .find(|p| p.pmr_id == pmr_id && p.pid == pid && p.status == "running")
+            else {
+                return false;
+            };
+            process.pid = 0;
+            process.start_time = 0;
+            process.last_exit_code = exit_code;
+            process.record_status("exited");
+            true
+        })
+    }
+
+    /// 更新健康状态，只有记录的PID仍是 pid 时才更新，避免把旧进程的结果写到新进程上
+    pub fn update_health(&self, pmr_id: u32, pid: u32, health: HealthState) -> io::Result<()> {
+        self.modify_process(pmr_id, |process| {
+            if process.pid == pid {
+                process.health = Some(health);
+            }
+        })
+    }
+
+    pub fn set_watch(&self, pmr_id: u32, watch: bool) -> io::Result<()> {
+        self.modify_process(pmr_id, |process| process.watch = watch)
+    }
+}
+
+/// pmr数据文件的咨询锁（flock），用于 dump.json 和事件日志，文件关闭时自动释放
+pub(crate) struct FileLock {
+    _file: fs::File,
+}
+
+impl FileLock {
+    pub(crate) fn acquire(path: &Path) -> io::Result<Self> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            while unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            }
         }
+        Ok(Self { _file: file })
     }
 }
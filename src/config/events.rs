@@ -0,0 +1,114 @@
+use super::super::base::event::Event;
+use super::dump::FileLock;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 事件日志保留的最近事件数量
+pub const MAX_EVENTS: usize = 1000;
+
+// 文件超过该大小时裁剪到最近的 MAX_EVENTS 条，避免每次写入都重写文件
+const MAX_EVENT_LOG_SIZE: u64 = 512 * 1024;
+
+pub fn get_event_log_path() -> io::Result<PathBuf> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, t!("home.not_found")))?;
+    let pmr_dir = home_dir.join(".pmr");
+
+    // 确保目录存在
+    if !pmr_dir.exists() {
+        fs::create_dir_all(&pmr_dir)?;
+    }
+
+    Ok(pmr_dir.join("events.log"))
+}
+
+/// 追加事件到 ~/.pmr/events.log（JSONL），超过大小上限时只保留最近的事件
+pub fn append_event(event: &Event) -> io::Result<()> {
+    append_to(&get_event_log_path()?, event)
+}
+
+fn append_to(path: &Path, event: &Event) -> io::Result<()> {
+    let json = serde_json::to_string(event).map_err(io::Error::other)?;
+    // 多个pmr进程同时写入时，避免裁剪丢失其他进程刚追加的事件
+    let _lock = FileLock::acquire(&path.with_extension("lock"))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", json)?;
+
+    if file.metadata()?.len() > MAX_EVENT_LOG_SIZE {
+        let contents = fs::read_to_string(path)?;
+        let lines: Vec<&str> = contents.lines().collect();
+        let keep = &lines[lines.len().saturating_sub(MAX_EVENTS)..];
+
+        // 先写临时文件再替换，避免读取方看到写了一半的文件
+        let tmp_path = path.with_extension(format!("log.{}.tmp", std::process::id()));
+        fs::write(&tmp_path, keep.join("\n") + "\n")?;
+        fs::rename(&tmp_path, path)?;
+    }
+    Ok(())
+}
+
+/// 解析一行事件日志，无法解析的行返回 None
+pub fn parse_event(line: &str) -> Option<Event> {
+    serde_json::from_str(line).ok()
+}
+
+/// 读取事件日志中的所有事件，文件不存在时返回空列表
+pub fn read_events() -> io::Result<Vec<Event>> {
+    let path = get_event_log_path()?;
+    match fs::read_to_string(&path) {
+        Ok(contents) => Ok(contents.lines().filter_map(parse_event).collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::base::event::EventKind;
+    use super::super::super::base::process::PmrProcessInfo;
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn concurrent_appends_survive_trimming() {
+        let dir = std::env::temp_dir().join(format!("pmr-test-events-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.log");
+
+        // 每条事件约 450 字节，写入过程中会裁剪多次
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    let process = PmrProcessInfo::new(
+                        format!("writer-{}", writer),
+                        "default".to_string(),
+                        String::new(),
+                        "sleep".to_string(),
+                        Vec::new(),
+                    );
+                    for i in 0..200 {
+                        let mut event = Event::new(EventKind::Start, &process, i.to_string());
+                        event.detail = Some("x".repeat(300));
+                        append_to(&path, &event).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let events: Vec<Event> = contents.lines().map(|l| parse_event(l).unwrap()).collect();
+        // 每个写入方的最后一条事件都不应该被其他写入方的裁剪覆盖
+        for writer in 0..8 {
+            let name = format!("writer-{}", writer);
+            assert!(events.iter().any(|e| e.name == name && e.reason == "199"));
+        }
+        let leftover = fs::read_dir(&dir).unwrap().count();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(leftover, 2); // events.log 和 events.lock
+    }
+}
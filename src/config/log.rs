@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 pub fn get_log_path(pmr_id: u32) -> io::Result<PathBuf> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Home directory not found"))?;
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, t!("home.not_found")))?;
     let log_dir = home_dir.join(".pmr").join("logs");
 
     // 确保日志目录存在
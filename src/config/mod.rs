@@ -1,2 +1,4 @@
 pub mod dump;
+pub mod events;
 pub mod log;
+pub mod settings;
@@ -0,0 +1,57 @@
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// 全局设置，保存在 ~/.pmr/config（JSON格式）
+#[derive(Deserialize, Default)]
+pub struct Settings {
+    #[serde(default)]
+    pub event_sinks: Vec<EventSink>,
+}
+
+/// 事件的接收方，events 为空时接收所有事件
+#[derive(Deserialize, Clone)]
+pub struct EventSink {
+    #[serde(flatten)]
+    pub target: SinkTarget,
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SinkTarget {
+    Webhook { url: String },     // 以JSON POST发送
+    Command { command: String }, // 通过标准输入传入事件JSON
+    File { path: String },       // 追加为JSONL
+}
+
+pub fn get_settings_path() -> io::Result<PathBuf> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, t!("home.not_found")))?;
+    Ok(home_dir.join(".pmr").join("config"))
+}
+
+/// 读取全局设置，文件不存在时使用默认值
+pub fn load_settings() -> io::Result<Settings> {
+    let path = get_settings_path()?;
+    match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                t!("config.parse_failed", path.display(), e),
+            )
+        }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(e),
+    }
+}
+
+/// 展开路径开头的 ~
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,155 @@
+use super::base::process::PmrProcessInfo;
+use std::fmt;
+use std::io;
+
+/// pmr 操作失败的原因，命令行根据 [`PmrError::exit_code`] 设置退出码
+#[derive(Debug)]
+pub enum PmrError {
+    /// 目标（pmr_id、名称、命名空间或 all）没有匹配的进程
+    NotFound(String),
+    /// 参数无效，例如空的程序名
+    InvalidArgument(String),
+    /// 配置文件不存在、无法解析或内容无效
+    Config(String),
+    /// 进程列表 ~/.pmr/dump.json 无法读取、解析或写入
+    Registry(io::Error),
+    /// 启动进程失败
+    StartFailed {
+        pmr_id: u32,
+        name: String,
+        message: String,
+    },
+    /// 停止进程失败
+    StopFailed {
+        pmr_id: u32,
+        name: String,
+        message: String,
+    },
+    /// 重启进程失败
+    RestartFailed {
+        pmr_id: u32,
+        name: String,
+        message: String,
+    },
+    /// 删除进程失败
+    DeleteFailed {
+        pmr_id: u32,
+        name: String,
+        message: String,
+    },
+    /// 目标包含多个进程时，部分进程操作失败，failed 中是每个进程的错误
+    Batch {
+        succeeded: Vec<u32>,
+        failed: Vec<PmrError>,
+    },
+    /// 其他读写错误，例如日志文件或网络
+    Io(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, PmrError>;
+
+impl PmrError {
+    pub fn start_failed(process: &PmrProcessInfo, message: impl fmt::Display) -> Self {
+        PmrError::StartFailed {
+            pmr_id: process.pmr_id,
+            name: process.name.clone(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn stop_failed(process: &PmrProcessInfo, message: impl fmt::Display) -> Self {
+        PmrError::StopFailed {
+            pmr_id: process.pmr_id,
+            name: process.name.clone(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn restart_failed(process: &PmrProcessInfo, message: impl fmt::Display) -> Self {
+        PmrError::RestartFailed {
+            pmr_id: process.pmr_id,
+            name: process.name.clone(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn delete_failed(process: &PmrProcessInfo, message: impl fmt::Display) -> Self {
+        PmrError::DeleteFailed {
+            pmr_id: process.pmr_id,
+            name: process.name.clone(),
+            message: message.to_string(),
+        }
+    }
+
+    /// 命令行的退出码，见 README 中的 Exit codes
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PmrError::Io(_) => 1,
+            PmrError::InvalidArgument(_) => 2,
+            PmrError::NotFound(_) => 3,
+            PmrError::Config(_) => 4,
+            PmrError::Registry(_) => 5,
+            PmrError::StartFailed { .. } => 6,
+            PmrError::StopFailed { .. } => 7,
+            PmrError::RestartFailed { .. } => 8,
+            PmrError::DeleteFailed { .. } => 9,
+            PmrError::Batch { .. } => 10,
+        }
+    }
+}
+
+impl fmt::Display for PmrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PmrError::NotFound(target) => write!(f, "{}", t!("error.not_found", target)),
+            PmrError::InvalidArgument(message) => {
+                write!(f, "{}", t!("error.invalid_argument", message))
+            }
+            PmrError::Config(message) => write!(f, "{}", t!("error.config", message)),
+            PmrError::Registry(e) => write!(f, "{}", t!("error.registry", e)),
+            PmrError::StartFailed {
+                pmr_id,
+                name,
+                message,
+            }
+            | PmrError::StopFailed {
+                pmr_id,
+                name,
+                message,
+            }
+            | PmrError::RestartFailed {
+                pmr_id,
+                name,
+                message,
+            }
+            | PmrError::DeleteFailed {
+                pmr_id,
+                name,
+                message,
+            } => write!(f, "{} ({}): {}", name, pmr_id, message),
+            PmrError::Batch { succeeded, failed } => {
+                write!(f, "{}", t!("batch.counts", succeeded.len(), failed.len()))?;
+                for error in failed {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+            PmrError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for PmrError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PmrError::Registry(e) | PmrError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PmrError {
+    fn from(e: io::Error) -> Self {
+        PmrError::Io(e)
+    }
+}
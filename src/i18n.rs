@@ -0,0 +1,355 @@
+//! 命令行输出的多语言文本。
+//!
+//! 语言按 `--lang`、`PMR_LANG`、`LANG` 的顺序取第一个受支持的值，以 `zh` 开头时使用简体中文，否则使用英文。
+//! 文本中的 `{}` 依次替换为 [`t!`] 的参数。
+
+use once_cell::sync::OnceCell;
+use std::env;
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    ZhCn,
+}
+
+impl Lang {
+    /// 传给子进程的 PMR_LANG 值
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::ZhCn => "zh-CN",
+        }
+    }
+}
+
+static LANG: OnceCell<Lang> = OnceCell::new();
+
+/// 解析语言名称，例如 `en`、`en_US.UTF-8`、`zh`、`zh-CN`、`zh_CN.UTF-8`
+pub fn parse_lang(value: &str) -> Result<Lang, String> {
+    let value = value.trim().to_ascii_lowercase();
+    if value.starts_with("zh") {
+        Ok(Lang::ZhCn)
+    } else if value.starts_with("en") || value == "c" || value.starts_with("c.") || value == "posix"
+    {
+        Ok(Lang::En)
+    } else {
+        Err(format!(
+            "unsupported language '{}', expected en or zh-CN",
+            value
+        ))
+    }
+}
+
+/// 设置输出语言，只在第一次输出之前生效
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+/// 当前输出语言，未通过 --lang 指定时读取 PMR_LANG 和 LANG
+pub fn lang() -> Lang {
+    *LANG.get_or_init(|| {
+        // 跳过未设置或不支持的值，例如 PMR_LANG=fr 时继续读取 LANG
+        ["PMR_LANG", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .filter(|value| !value.is_empty())
+            .find_map(|value| parse_lang(&value).ok())
+            .unwrap_or(Lang::En)
+    })
+}
+
+/// 当前语言下 key 对应的文本，未收录的 key 原样返回
+pub fn text(key: &str) -> &str {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, en, zh)) => match lang() {
+            Lang::En => en,
+            Lang::ZhCn => zh,
+        },
+        None => key,
+    }
+}
+
+/// 依次用参数替换文本中的 `{}`
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(index) = rest.find("{}") {
+        result.push_str(&rest[..index]);
+        match args.next() {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str("{}"),
+        }
+        rest = &rest[index + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 当前语言下的文本：`t!("stop.stopped", name, pid)`
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::text($key).to_string()
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::text($key), &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+/// (key, English, 简体中文)
+#[rustfmt::skip]
+static MESSAGES: &[(&str, &str, &str)] = &[
+    // error
+    ("error.not_found", "no process found: {}", "未找到进程: {}"),
+    ("error.invalid_argument", "invalid argument: {}", "参数无效: {}"),
+    ("error.config", "invalid config file: {}", "配置文件无效: {}"),
+    ("error.registry", "cannot read or write the process list: {}", "无法读写进程列表: {}"),
+    ("batch.counts", "{} succeeded, {} failed", "{} 成功, {} 失败"),
+    // stop
+    ("stop.action", "Stop", "停止进程"),
+    ("list.current", "Current process list:", "当前进程列表:"),
+    ("hook.process_failed", "process '{}': {}", "进程 '{}' 的 {}"),
+    ("stop.already_stopped", "Process '{}' is already stopped", "进程 '{}' 已经停止"),
+    ("stop.not_running", "Process '{}' (PID: {}) is not running", "进程 '{}' (PID: {}) 未在运行"),
+    ("stop.kill_command_failed", "cannot run the kill command: {}", "无法执行进程终止命令: {}"),
+    ("stop.stopped", "Stopped process '{}' (PID: {})", "已停止进程 '{}' (PID: {})"),
+    ("stop.kill_failed", "cannot kill PID {}: {}", "无法结束 PID {}: {}"),
+    ("process.update_status_failed", "cannot update process status: {}", "无法更新进程状态: {}"),
+    // delete
+    ("delete.action", "Delete", "删除进程"),
+    ("delete.deleted", "Deleted process '{}'", "已删除进程 '{}'"),
+    // restart
+    ("restart.action", "Restart", "重启进程"),
+    ("restart.restarted", "Process '{}' restarted, new PID: {}", "进程 '{}' 重启成功，新 PID: {}"),
+    ("restart.update_restarts_failed", "cannot update the restart count: {}", "无法更新重启次数: {}"),
+    // reload
+    ("reload.aborted", "Failed to reload process '{}' (pmr_id: {}), rolling restart aborted, {} process(es) not reloaded", "进程 '{}' (pmr_id: {}) 重载失败，已中止滚动重启，剩余 {} 个进程未重载"),
+    ("reload.reloading", "Reloading process '{}'...", "正在重载进程 '{}'..."),
+    ("reload.not_ready", "New process '{}' (PID: {}) is not ready: {}", "新进程 '{}' (PID: {}) 未就绪: {}"),
+    ("reload.reloaded", "Process '{}' reloaded, new PID: {}", "进程 '{}' 重载成功，新 PID: {}"),
+    ("reload.exited", "process exited ({})", "进程已退出 ({})"),
+    ("reload.unhealthy", "health check did not pass within {}ms: {}", "{}ms 内健康检查未通过: {}"),
+    ("reload.ambiguous_ready", "wait_ready {} cannot tell the new process from the old one, use restart or notify readiness", "wait_ready {} 无法区分新旧进程，请使用 restart 或 notify 就绪信号"),
+    // scale
+    ("scale.unchanged", "Process '{}' already has {} instance(s)", "进程 '{}' 已有 {} 个实例"),
+    ("scale.up", "Process '{}' scaled up: {} -> {}", "进程 '{}' 已扩容: {} -> {}"),
+    ("scale.down", "Process '{}' scaled down: {} -> {}", "进程 '{}' 已缩容: {} -> {}"),
+    ("scale.invalid_count", "invalid instance count '{}', expected N, +N or -N", "无效的实例数量 '{}'，应为 N、+N 或 -N"),
+    // start
+    ("start.invalid_instances", "invalid instances '{}', expected a number or max", "无效的实例数 '{}'，应为数字或 max"),
+    ("start.action", "Start", "启动进程"),
+    ("start.program_empty", "program is empty", "程序为空"),
+    ("config.read_failed", "cannot read {}: {}", "无法读取 {}: {}"),
+    ("config.parse_failed", "cannot parse {}: {}", "无法解析 {}: {}"),
+    ("start.config_or_target", "either --config or target is required", "必须指定 --config 或 target"),
+    ("start.dependency_not_running", "'{}' depends on '{}', which is not running", "进程 '{}' 依赖的 '{}' 未在运行"),
+    ("start.waiting_health", "Waiting for the health check of '{}' to pass...", "等待进程 '{}' 健康检查通过..."),
+    ("start.dependency_unhealthy", "dependency did not pass its health check within {}ms: {}", "依赖的进程未在 {}ms 内通过健康检查: {}"),
+    ("start.dependency_exited", "dependency exited after starting", "依赖的进程启动后已退出"),
+    ("ready.waiting", "Waiting for process '{}' to be ready...", "等待进程 '{}' 就绪..."),
+    ("ready.timeout", "Process '{}' was not ready within {}ms, treating it as started", "进程 '{}' 未在 {}ms 内就绪，仍视为已启动"),
+    ("start.already_running", "Process '{}' is already running, PID: {}", "进程 '{}' 已经在运行中，PID: {}"),
+    ("start.started", "Started process '{}' PID: {}", "启动进程 '{}' PID: {}"),
+    // api
+    ("api.already_running", "the control API is already running: {}", "控制接口已在运行: {}"),
+    ("api.started", "Control API listening on {}", "控制接口已启动: {}"),
+    ("api.connection_failed", "Failed to handle connection: {}", "处理连接失败: {}"),
+    ("server.accept_failed", "Failed to accept connection: {}", "接受连接失败: {}"),
+    ("api.unix_only", "the control API is only supported on Unix", "控制接口仅支持Unix系统"),
+    // daemon
+    ("daemon.started", "pmr daemon started, checking every {}s, press Ctrl+C to exit", "pmr daemon 已启动，每 {}s 检查一次，按 Ctrl+C 退出"),
+    ("list.read_failed", "Failed to read the process list: {}", "读取进程列表失败: {}"),
+    ("exit.code", "exited with code {}", "退出码 {}"),
+    ("exit.signal", "killed by signal {}", "被信号 {} 终止"),
+    ("exit.exited", "exited", "已退出"),
+    ("daemon.exited", "Process '{}' (PID: {}) exited: {}", "进程 '{}' (PID: {}) 已退出: {}"),
+    ("daemon.update_status_failed", "Failed to update the status of process '{}': {}", "更新进程 '{}' 状态失败: {}"),
+    ("daemon.memory_limit", "Process '{}' uses {} MB, over the limit of {} MB, restarting", "进程 '{}' 内存 {} MB 超过上限 {} MB，正在重启"),
+    ("daemon.cron_restart", "Process '{}' reached its scheduled restart ({}), restarting", "进程 '{}' 到达定时重启时间 ({})，正在重启"),
+    ("restart.failed", "Failed to restart process '{}': {}", "重启进程失败 '{}': {}"),
+    // health
+    ("health.recovered", "Health check of process '{}' recovered: {}", "进程 '{}' 健康检查恢复正常: {}"),
+    ("health.failed", "Health check of process '{}' failed {} times in a row, marked unhealthy: {}", "进程 '{}' 健康检查连续失败 {} 次，标记为 unhealthy: {}"),
+    ("health.update_failed", "Failed to update the health of process '{}': {}", "更新进程 '{}' 健康状态失败: {}"),
+    ("health.restarting", "Process '{}' is unhealthy, restarting", "进程 '{}' 不健康，正在重启"),
+    // watch
+    ("watch.enabled", "File watching enabled for process '{}'", "进程 '{}' 文件监听已开启"),
+    ("watch.disabled", "File watching disabled for process '{}'", "进程 '{}' 文件监听已关闭"),
+    ("watch.watch_failed", "Failed to watch the files of process '{}': {}", "监听进程 '{}' 的文件失败: {}"),
+    ("watch.watching", "Watching the files of process '{}': {}", "开始监听进程 '{}' 的文件: {}"),
+    ("watch.changed", "Files of process '{}' changed, restarting", "检测到进程 '{}' 的文件变化，正在重启"),
+    ("watch.invalid_ignore", "Invalid ignore_watch pattern '{}': {}", "无效的 ignore_watch 规则 '{}': {}"),
+    // events
+    ("events.empty", "No events recorded", "没有记录的事件"),
+    ("events.no_pid", "no pid", "无 pid"),
+    ("events.spawned", "spawned with {}", "已启动，{}"),
+    ("events.restarted", "restarted as {} ({})", "已重启为 {} ({})"),
+    ("events.crash_loop", "{} failures within {}s, last: {}", "{} 次失败（{}s 内），最近一次: {}"),
+    ("events.health_failures", "{} consecutive failures: {}", "连续失败 {} 次: {}"),
+    ("events.memory_limit", "memory {} MB exceeded limit {} MB", "内存 {} MB 超过上限 {} MB"),
+    ("events.stopped_by_user", "stopped by user", "用户停止"),
+    ("events.deleted", "deleted", "已删除"),
+    ("events.scaled_down", "scaled down", "缩容"),
+    // metrics
+    ("metrics.started", "Metrics server listening on {}", "指标服务已启动: {}"),
+    ("metrics.request_failed", "Failed to handle metrics request: {}", "处理指标请求失败: {}"),
+    // list
+    ("ctrlc.failed", "cannot set the Ctrl+C handler: {}", "无法设置Ctrl+C处理器: {}"),
+    ("list.refreshing", "Refreshing every {}s, press Ctrl+C to exit", "每 {}s 刷新一次，按 Ctrl+C 退出"),
+    ("list.invalid_sort", "invalid sort order '{}', expected asc or desc", "无效的排序方向 '{}'，应为 asc 或 desc"),
+    ("list.mode_fork", "fork", "fork"),
+    ("list.mode_cluster", "cluster #{}", "集群 #{}"),
+    ("column.id", "id", "编号"),
+    ("column.name", "name", "名称"),
+    ("column.namespace", "namespace", "命名空间"),
+    ("column.version", "version", "版本"),
+    ("column.mode", "mode", "模式"),
+    ("column.pid", "pid", "pid"),
+    ("column.uptime", "uptime", "运行时长"),
+    ("column.restarts", "restarts", "重启"),
+    ("column.status", "status", "状态"),
+    ("column.cpu", "cpu", "CPU"),
+    ("column.mem", "mem", "内存"),
+    ("column.user", "user", "用户"),
+    ("column.field", "field", "字段"),
+    ("column.value", "value", "值"),
+    ("column.cron", "cron", "cron"),
+    ("column.next", "next", "下次重启"),
+    ("column.in", "in", "剩余"),
+    // schedule
+    ("schedule.empty", "No process has a scheduled restart", "没有设置定时重启的进程"),
+    // log
+    ("log.exit", "Stopped viewing logs", "退出日志查看"),
+    ("log.path_failed", "Cannot get the log file path: {}", "无法获取日志文件路径: {}"),
+    ("log.missing", "Log file does not exist: {}", "日志文件不存在: {}"),
+    ("log.open_failed", "Cannot open the log file: {}", "无法打开日志文件: {}"),
+    ("log.seek_failed", "Cannot seek to the end of the log file: {}", "无法定位到文件末尾: {}"),
+    ("log.viewing", "Viewing log file: {}", "正在查看日志文件: {}"),
+    ("log.hint", "Press Ctrl+C to stop viewing logs...", "按 Ctrl+C 退出日志查看..."),
+    // monit
+    ("monit.action_succeeded", "{} {} succeeded", "{} {} 成功"),
+    ("monit.action_failed", "{} {} failed: {}", "{} {} 失败: {}"),
+    ("monit.delete_cancelled", "Cancelled deleting '{}'", "已取消删除 '{}'"),
+    ("monit.delete_confirm", "Delete '{}'? (y/n)", "确认删除 '{}'? (y/n)"),
+    ("monit.logs", "logs: {} ({})", "日志: {} ({})"),
+    ("monit.empty", "No processes managed by pmr", "没有由pmr管理的进程"),
+    ("monit.footer", "↑/↓ select  r restart  s stop  d delete  q quit", "↑/↓ 选择  r 重启  s 停止  d 删除  q 退出"),
+    // describe
+    ("describe.name", "name", "名称"),
+    ("describe.namespace", "namespace", "命名空间"),
+    ("describe.status", "status", "状态"),
+    ("describe.cpu", "cpu", "CPU"),
+    ("describe.program", "program", "程序"),
+    ("describe.args", "args", "参数"),
+    ("describe.workdir", "workdir", "工作目录"),
+    ("describe.user", "user", "用户"),
+    ("describe.user_value", "{} (group: {}, groups: {})", "{}（主组：{}，附加组：{}）"),
+    ("describe.env_keys", "env keys", "环境变量"),
+    ("describe.log_path", "log path", "日志路径"),
+    ("describe.restarts", "restarts", "重启次数"),
+    ("describe.restart_history", "restart history", "重启历史"),
+    ("describe.watch", "watch", "文件监听"),
+    ("describe.cron_restart", "cron restart", "定时重启"),
+    ("describe.max_memory_restart", "max memory restart", "内存上限"),
+    ("describe.depends_on", "depends on", "依赖"),
+    ("describe.hooks", "hooks", "钩子"),
+    ("describe.wait_ready", "wait ready", "就绪信号"),
+    ("describe.health_check", "health check", "健康检查"),
+    ("describe.health", "health", "健康状态"),
+    ("describe.uptime", "uptime", "运行时间"),
+    ("describe.mem", "mem", "内存"),
+    ("describe.last_exit_code", "last exit code", "最后退出码"),
+    ("describe.status_history", "status history", "状态历史"),
+    ("describe.config_source", "config source", "配置来源"),
+    ("describe.ago", "{} ({} ago)", "{} ({}前)"),
+    ("describe.hook", "{}: {} (timeout {}ms)", "{}: {} (超时 {}ms)"),
+    ("describe.disabled", "disabled", "未开启"),
+    ("describe.watch_paths", "{} (ignore: {})", "{} (忽略: {})"),
+    ("describe.wait_ready_value", "{} (timeout: {}ms)", "{} (超时: {}ms)"),
+    ("describe.health_check_value", "{}{} (every {}ms, timeout {}ms, threshold {}{})", "{}{} (每 {}ms，超时 {}ms，阈值 {}{})"),
+    ("describe.expect", " expect {}", " 期望 {}"),
+    ("describe.auto_restart", ", auto restart", "，自动重启"),
+    ("describe.health_value", "{} ({} consecutive failures): {} ({} ago)", "{} (连续失败 {} 次): {} ({}前)"),
+    ("describe.command_line", "command line", "命令行"),
+    // main
+    ("main.init_failed", "Failed to initialize the .pmr directory: {}", "无法初始化 .pmr 目录: {}"),
+    ("main.config_or_target", "Error: either --config or target is required", "错误: 必须指定 --config 或 target"),
+    ("main.start_failed", "Failed to start process", "启动进程失败"),
+    ("main.refresh_failed", "Failed to refresh the process list", "刷新进程列表失败"),
+    ("main.list_failed", "Failed to read the process list", "读取进程列表失败"),
+    ("main.delete_failed", "Failed to delete process", "删除进程失败"),
+    ("main.stop_failed", "Failed to stop process", "停止进程失败"),
+    ("main.restart_failed", "Failed to restart process", "重启进程失败"),
+    ("main.reload_failed", "Failed to reload process", "重载进程失败"),
+    ("main.describe_failed", "Failed to describe process", "查看进程详情失败"),
+    ("main.monit_failed", "Failed to start the monitor", "启动监控界面失败"),
+    ("main.schedule_failed", "Failed to list scheduled restarts", "查看定时重启失败"),
+    ("main.api_failed", "Control API failed", "控制接口运行失败"),
+    ("main.metrics_failed", "Failed to export metrics", "导出指标失败"),
+    ("main.watch_failed", "Failed to update file watching", "更新文件监听失败"),
+    ("main.daemon_failed", "Daemon failed", "守护进程运行失败"),
+    ("main.scale_failed", "Failed to scale process", "调整实例数量失败"),
+    ("main.log_failed", "Failed to view logs", "查看日志失败"),
+    ("main.events_failed", "Failed to view events", "查看事件失败"),
+    // base
+    ("dependency.cycle", "dependency cycle detected: {}", "检测到循环依赖: {}"),
+    ("event.log_failed", "Failed to write the event log: {}", "写入事件日志失败: {}"),
+    ("event.settings_failed", "Failed to read global settings: {}", "读取全局设置失败: {}"),
+    ("event.serialize_failed", "Failed to serialize event: {}", "序列化事件失败: {}"),
+    ("event.deliver_failed", "Failed to deliver {} event: {}", "发送 {} 事件失败: {}"),
+    ("event.webhook_status", "webhook returned HTTP {}", "webhook 返回 HTTP {}"),
+    ("command.exited", "command exited ({})", "命令退出 ({})"),
+    ("command.timed_out", "command timed out", "命令超时"),
+    ("hook.failed", "{} hook failed: {}", "{} 钩子失败: {}"),
+    ("hook.timed_out", "command did not finish within {}ms", "命令超过 {}ms 未结束"),
+    ("http.resolve_failed", "cannot resolve address: {}", "无法解析地址: {}"),
+    ("http.invalid_response", "invalid HTTP response", "无效的HTTP响应"),
+    ("http.invalid_url", "invalid url '{}', expected http://host[:port]/path", "无效的url '{}'，应为 http://host[:port]/path"),
+    ("http.invalid_port", "invalid port in url '{}'", "url '{}' 中的端口无效"),
+    ("health.invalid_address", "invalid health check address '{}'", "无效的健康检查地址 '{}'"),
+    ("health.invalid_spec", "invalid health check '{}', expected http://host[:port]/path, tcp:[host:]port or cmd:<command>", "无效的健康检查 '{}'，应为 http://host[:port]/path、tcp:[host:]port 或 cmd:<command>"),
+    ("health.connected", "connected to {}", "已连接 {}"),
+    ("memory.invalid_size", "invalid memory size '{}'", "无效的内存大小 '{}'"),
+    ("memory.invalid_unit", "invalid memory unit in '{}', expected K, M or G", "'{}' 中的内存单位无效，应为 K、M 或 G"),
+    ("schedule.invalid_cron", "invalid cron expression '{}': {}", "无效的cron表达式 '{}': {}"),
+    ("ready.invalid_regex", "invalid wait_ready regex '{}': {}", "无效的 wait_ready 正则 '{}': {}"),
+    ("ready.invalid_port", "invalid wait_ready port '{}'", "无效的 wait_ready 端口 '{}'"),
+    ("ready.invalid_spec", "invalid wait_ready '{}', expected log:<regex>, file:<path>, port:<port> or notify", "无效的 wait_ready '{}'，应为 log:<regex>、file:<path>、port:<port> 或 notify"),
+    ("ready.exited", "process exited before it was ready ({})", "进程在就绪前退出 ({})"),
+    ("ready.timed_out", "timed out waiting for the process to be ready", "等待就绪超时"),
+    ("ready.notify_unix_only", "wait_ready notify is only supported on Unix", "wait_ready notify 仅支持Unix系统"),
+    ("user.read_failed", "cannot read {}: {}", "无法读取 {}: {}"),
+    ("user.not_found", "user '{}' not found in {}", "用户 '{}' 不在 {} 中"),
+    ("user.group_not_found", "group '{}' not found in {}", "组 '{}' 不在 {} 中"),
+    ("user.requires_root", "only root can run processes as another user or group", "只有 root 可以以其他用户或组运行进程"),
+    ("user.unix_only", "running as another user is only supported on Unix", "以其他用户运行仅支持Unix系统"),
+    // config
+    ("home.not_found", "Home directory not found", "找不到用户主目录"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_replaces_placeholders_in_order() {
+        assert_eq!(fill("{} of {}", &[&1, &"two"]), "1 of two");
+        assert_eq!(fill("no placeholders", &[&1]), "no placeholders");
+        // 参数不足时保留剩余的占位符
+        assert_eq!(fill("{} and {}", &[&"a"]), "a and {}");
+        assert_eq!(fill("{}{}", &[&"{}", &"x"]), "{}x");
+    }
+
+    #[test]
+    fn languages() {
+        for value in ["zh", "zh-CN", "zh_CN.UTF-8", "ZH_tw"] {
+            assert_eq!(parse_lang(value), Ok(Lang::ZhCn), "{}", value);
+        }
+        for value in ["en", "en_US.UTF-8", "C", "C.UTF-8", "POSIX"] {
+            assert_eq!(parse_lang(value), Ok(Lang::En), "{}", value);
+        }
+        assert!(parse_lang("fr_FR.UTF-8").is_err());
+    }
+}
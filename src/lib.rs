@@ -0,0 +1,23 @@
+//! Process Manager in Rust.
+//!
+//! `pmr` 命令行工具的实现，也可以作为库嵌入其他程序：[`ProcessManager`] 提供启动、停止、
+//! 重启、删除和列出进程的接口，与命令行共用 `~/.pmr` 下的进程列表和日志。
+
+#[macro_use]
+pub mod i18n;
+
+pub(crate) mod base;
+#[doc(hidden)]
+pub mod cli;
+pub(crate) mod commands;
+pub(crate) mod config;
+pub mod error;
+pub mod manager;
+
+pub use base::health::{HealthCheck, HealthState};
+pub use base::hook::{Hook, Hooks};
+pub use base::process::{PmrProcessInfo, RestartRecord, StatusChange};
+pub use commands::list::{PmrProcess, ProcessEntry, ProcessMonit};
+pub use commands::start::StartOptions;
+pub use error::{PmrError, Result};
+pub use manager::{ProcessManager, ProcessSpec};
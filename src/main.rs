@@ -1,31 +1,43 @@
 use clap::{Parser, Subcommand};
+use pmr::cli::{
+    config_init, delete_process, describe_process, flush_events, list_schedules, monit,
+    parse_instances, parse_memory_size, print_metrics, print_processes, reload_process,
+    restart_process, run_daemon, scale_process, serve_api, serve_metrics, set_watch, show_events,
+    start_process, stop_process, tail_log, validate_cron, validate_group, validate_health_check,
+    validate_ready_signal, validate_user, watch_processes, ListFormat, ListOptions, SortSpec,
+};
+use pmr::i18n::{self, parse_lang, Lang};
+use pmr::{t, PmrError, StartOptions};
 use std::path::PathBuf;
-mod base;
-mod commands;
-mod config;
-use commands::delete::delete_process;
-use commands::list::list_processes;
-use commands::restart::restart_process;
-use commands::start::start_process;
-use commands::stop::stop_process;
-use commands::tail_log;
-use config::dump::DumpConfig;
-
-fn config_init() -> std::io::Result<()> {
-    // 使用DumpConfig初始化配置
-    let _ = DumpConfig::get_instance();
-    Ok(())
+use std::time::Duration;
+
+/// 命令失败时打印错误并按错误类型设置退出码，context 为消息目录中的 key，
+/// 多个进程部分失败时 print_batch 已经打印了每个进程的错误
+fn exit_on_error<E: Into<PmrError>>(context: &str, result: Result<(), E>) {
+    if let Err(e) = result {
+        let e = e.into();
+        if !matches!(e, PmrError::Batch { .. }) {
+            eprintln!("{}: {}", t!(context), e);
+        }
+        flush_events();
+        std::process::exit(e.exit_code());
+    }
 }
 
 #[derive(Parser)]
 #[command(name = "pmr")]
 #[command(about = "Process Manager in Rust", long_about = None)]
 struct Cli {
+    /// Output language: en or zh-CN (defaults to PMR_LANG, then LANG)
+    #[arg(long, global = true, value_parser = parse_lang)]
+    lang: Option<Lang>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     /// Start a process
     Start {
@@ -41,9 +53,105 @@ enum Commands {
         #[arg(long, default_value = "default")]
         namespace: String,
 
-        /// Target (can be pmr_id, name, or program to run)
+        /// Target (can be pmr_id, name, namespace, "all", or program to run)
         target: Option<String>,
 
+        /// Launch N instances of the program as a group ("max" = number of CPUs)
+        #[arg(short, long, value_name = "N", value_parser = parse_instances)]
+        instances: Option<u32>,
+
+        /// Restart the process when its memory exceeds this size (e.g. 512M, 1G)
+        #[arg(long, value_name = "SIZE", value_parser = parse_memory_size)]
+        max_memory_restart: Option<u64>,
+
+        /// Restart the process on a cron schedule (5 or 6 fields, e.g. "0 3 * * *")
+        #[arg(long, value_name = "CRON", value_parser = validate_cron)]
+        cron_restart: Option<String>,
+
+        /// Restart the process when files change, optionally limited to comma-separated paths
+        #[arg(
+            long,
+            value_name = "PATHS",
+            num_args = 0..=1,
+            require_equals = true,
+            value_delimiter = ',',
+            default_missing_value = ""
+        )]
+        watch: Option<Vec<String>>,
+
+        /// Glob of paths to ignore when watching (default: node_modules, .git, target)
+        #[arg(long, value_name = "GLOB")]
+        ignore_watch: Vec<String>,
+
+        /// Milliseconds to wait after the last change before restarting
+        #[arg(long, value_name = "MS")]
+        watch_delay: Option<u64>,
+
+        /// Only mark the process running after it signals readiness:
+        /// log:<regex>, file:<path>, port:<port> or notify (READY=1 on NOTIFY_SOCKET)
+        #[arg(long, value_name = "SIGNAL", value_parser = validate_ready_signal)]
+        wait_ready: Option<String>,
+
+        /// Milliseconds to wait for the ready signal (default 3000)
+        #[arg(long, value_name = "MS")]
+        listen_timeout: Option<u64>,
+
+        /// Health check run by pmr daemon: http://host[:port]/path, tcp:[host:]port or cmd:<command>
+        #[arg(long, value_name = "CHECK", value_parser = validate_health_check)]
+        health_check: Option<String>,
+
+        /// HTTP status expected from the health check (default: any 2xx)
+        #[arg(long, value_name = "CODE")]
+        health_status: Option<u16>,
+
+        /// Milliseconds between health checks (default 10000)
+        #[arg(long, value_name = "MS")]
+        health_interval: Option<u64>,
+
+        /// Milliseconds before a health check times out (default 2000)
+        #[arg(long, value_name = "MS")]
+        health_timeout: Option<u64>,
+
+        /// Consecutive failures before the process is unhealthy (default 3)
+        #[arg(long, value_name = "N")]
+        health_threshold: Option<u32>,
+
+        /// Restart the process when it becomes unhealthy
+        #[arg(long)]
+        health_restart: bool,
+
+        /// Command run in the workdir before starting; a failure aborts the start
+        #[arg(long, value_name = "CMD")]
+        pre_start: Option<String>,
+
+        /// Command run after the process is started and ready
+        #[arg(long, value_name = "CMD")]
+        post_start: Option<String>,
+
+        /// Command run before the process is stopped
+        #[arg(long, value_name = "CMD")]
+        pre_stop: Option<String>,
+
+        /// Command run after the process is stopped
+        #[arg(long, value_name = "CMD")]
+        post_stop: Option<String>,
+
+        /// Milliseconds before a hook command is killed (default 30000)
+        #[arg(long, value_name = "MS")]
+        hook_timeout: Option<u64>,
+
+        /// Run the process as this user (name or uid, requires root)
+        #[arg(long, value_name = "USER", value_parser = validate_user)]
+        user: Option<String>,
+
+        /// Primary group of the process (default: the user's primary group)
+        #[arg(long, value_name = "GROUP", value_parser = validate_group)]
+        group: Option<String>,
+
+        /// Comma separated supplementary groups (default: the user's groups in /etc/group)
+        #[arg(long, value_name = "GROUPS", value_delimiter = ',', value_parser = validate_group)]
+        groups: Vec<String>,
+
         /// Arguments for the program
         #[arg(last = true)]
         args: Vec<String>,
@@ -60,6 +168,47 @@ enum Commands {
         /// Show all system processes
         #[arg(long)]
         system: bool,
+
+        /// Output as JSON (same as --format json)
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        format: ListFormat,
+
+        /// Only show processes in this namespace
+        #[arg(long)]
+        namespace: Option<String>,
+
+        /// Only show processes with this status
+        #[arg(long)]
+        status: Option<String>,
+
+        /// Only show processes whose name matches this glob
+        #[arg(long)]
+        name: Option<glob::Pattern>,
+
+        /// Sort by id, name, cpu, mem, uptime or restarts, optionally suffixed with :asc or :desc
+        #[arg(long, value_name = "FIELD[:asc|desc]")]
+        sort: Option<SortSpec>,
+
+        /// Redraw the table every INTERVAL seconds (default 2)
+        #[arg(
+            long,
+            value_name = "INTERVAL",
+            num_args = 0..=1,
+            default_missing_value = "2",
+            conflicts_with_all = ["json", "format"]
+        )]
+        watch: Option<f64>,
+    },
+
+    /// List processes as JSON
+    Jlist {
+        /// Show all system processes
+        #[arg(long)]
+        system: bool,
     },
 
     /// Delete a process
@@ -69,13 +218,13 @@ enum Commands {
         about = "Delete a process. Alias: rm, del"
     )]
     Delete {
-        /// Process ID or name
+        /// Process ID, name, namespace or "all"
         target: String,
     },
 
     /// Stop a process
     Stop {
-        /// Process ID or name
+        /// Process ID, name, namespace or "all"
         target: String,
     },
 
@@ -89,7 +238,7 @@ enum Commands {
         #[arg(long, default_value = "default")]
         namespace: String,
 
-        /// Target (can be pmr_id, name, or program to run)
+        /// Target (can be pmr_id, name, namespace, "all", or program to run)
         target: Option<String>,
 
         /// Arguments for the program
@@ -97,21 +246,159 @@ enum Commands {
         args: Vec<String>,
     },
 
+    /// Reload processes one at a time without downtime
+    Reload {
+        /// Target (can be pmr_id, name, namespace, or "all")
+        target: String,
+    },
+
+    /// Show details of a process
+    #[command(
+        alias = "show",
+        alias = "info",
+        about = "Show details of a process. Alias: show, info"
+    )]
+    Describe {
+        /// Process ID or name
+        target: String,
+    },
+
+    /// Real-time dashboard of managed processes
+    Monit,
+
+    /// Manage scheduled restarts
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommands,
+    },
+
+    /// Local control API over a Unix socket
+    Api {
+        #[command(subcommand)]
+        command: ApiCommands,
+    },
+
+    /// Export process metrics in Prometheus text format
+    Metrics {
+        #[command(subcommand)]
+        command: MetricsCommands,
+    },
+
+    /// Turn file watching on or off for a process
+    Watch {
+        #[arg(value_enum)]
+        state: WatchState,
+
+        /// Process ID, name, namespace or "all"
+        target: String,
+    },
+
+    /// Run the supervisor that enforces memory limits, scheduled restarts and file watching
+    Daemon {
+        /// Seconds between checks
+        #[arg(long, default_value_t = 5.0)]
+        interval: f64,
+    },
+
+    /// Scale a process group to N instances, or by +N / -N
+    Scale {
+        /// Process name
+        name: String,
+
+        /// Target instance count: N, +N or -N
+        #[arg(allow_hyphen_values = true)]
+        count: String,
+    },
+
     /// View logs of a process
     #[command(alias = "logs")]
     Log {
-        /// Process ID or name
+        /// Process ID, name, namespace or "all"
         target: String,
     },
+
+    /// Show the timeline of process lifecycle events
+    Events {
+        /// Process ID, name or namespace; shows all processes if omitted
+        target: Option<String>,
+
+        /// Number of recent events to show
+        #[arg(short = 'n', long, default_value_t = 50)]
+        lines: usize,
+
+        /// Keep printing new events as they happen
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Print events as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
 }
 
-fn main() {
-    if let Err(e) = config_init() {
-        eprintln!("Failed to initialize .pmr directory: {}", e);
+#[derive(clap::ValueEnum, Clone, Copy)]
+enum WatchState {
+    On,
+    Off,
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommands {
+    /// Show the next scheduled restart of each process
+    #[command(alias = "ls")]
+    List,
+}
+
+#[derive(Subcommand)]
+enum ApiCommands {
+    /// Serve the line-delimited JSON API on ~/.pmr/pmr.sock
+    Serve,
+}
+
+#[derive(Subcommand)]
+enum MetricsCommands {
+    /// Serve metrics over HTTP at /metrics
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 9209)]
+        port: u16,
+
+        /// Address to bind to
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+    /// Print the current metrics once
+    Show,
+}
+
+/// 在clap解析之前应用 --lang，参数校验的错误信息也使用指定语言
+fn preset_lang() {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--lang") {
+            Some("") => args.next(),
+            Some(value) => value.strip_prefix('=').map(str::to_string),
+            None if arg == "--" => return,
+            None => continue,
+        };
+        if let Some(lang) = value.and_then(|value| parse_lang(&value).ok()) {
+            i18n::set_lang(lang);
+        }
         return;
     }
+}
 
+fn main() {
+    preset_lang();
     let cli = Cli::parse();
+    if let Some(lang) = cli.lang {
+        i18n::set_lang(lang);
+    }
+
+    if let Err(e) = config_init() {
+        eprintln!("{}", t!("main.init_failed", e));
+        std::process::exit(PmrError::Registry(e).exit_code());
+    }
 
     match cli.command {
         Commands::Start {
@@ -119,28 +406,105 @@ fn main() {
             name,
             namespace,
             target,
+            instances,
+            max_memory_restart,
+            cron_restart,
+            watch,
+            ignore_watch,
+            watch_delay,
+            wait_ready,
+            listen_timeout,
+            health_check,
+            health_status,
+            health_interval,
+            health_timeout,
+            health_threshold,
+            health_restart,
+            pre_start,
+            post_start,
+            pre_stop,
+            post_stop,
+            hook_timeout,
+            user,
+            group,
+            groups,
             args,
         } => {
             if config.is_none() && target.is_none() {
-                eprintln!("错误: 必须指定 --config 或 target");
-                return;
+                eprintln!("{}", t!("main.config_or_target"));
+                std::process::exit(2);
             }
-            match start_process(config, name, namespace, target, args) {
-                Ok(_) => (),
-                Err(e) => {
-                    eprintln!("启动进程失败: {}", e);
-                    std::process::exit(1);
+            let options = StartOptions {
+                max_memory_restart,
+                cron_restart,
+                watch,
+                ignore_watch,
+                watch_delay,
+                instances,
+                wait_ready,
+                listen_timeout,
+                health_check,
+                health_status,
+                health_interval,
+                health_timeout,
+                health_threshold,
+                health_restart,
+                pre_start,
+                post_start,
+                pre_stop,
+                post_stop,
+                hook_timeout,
+                user,
+                group,
+                groups,
+            };
+            exit_on_error(
+                "main.start_failed",
+                start_process(config, name, namespace, target, args, options),
+            );
+        }
+        Commands::List {
+            system,
+            json,
+            format,
+            namespace,
+            status,
+            name,
+            sort,
+            watch,
+        } => {
+            let format = if json { ListFormat::Json } else { format };
+            let options = ListOptions {
+                namespace,
+                status,
+                name,
+                sort,
+            };
+            match watch {
+                Some(interval) => {
+                    let interval = Duration::from_secs_f64(interval.max(0.5));
+                    exit_on_error(
+                        "main.refresh_failed",
+                        watch_processes(system, &options, interval),
+                    );
                 }
+                None => exit_on_error(
+                    "main.list_failed",
+                    print_processes(system, format, &options),
+                ),
             }
         }
-        Commands::List { system } => {
-            list_processes(system);
+        Commands::Jlist { system } => {
+            exit_on_error(
+                "main.list_failed",
+                print_processes(system, ListFormat::Json, &ListOptions::default()),
+            );
         }
         Commands::Delete { target } => {
-            delete_process(&target);
+            exit_on_error("main.delete_failed", delete_process(&target));
         }
         Commands::Stop { target } => {
-            stop_process(&target, true);
+            exit_on_error("main.stop_failed", stop_process(&target, true));
         }
         Commands::Restart {
             config,
@@ -149,22 +513,65 @@ fn main() {
             args,
         } => {
             if config.is_none() && target.is_none() {
-                eprintln!("错误: 必须指定 --config 或 target");
-                return;
-            }
-            match restart_process(config, Some(namespace), target, args) {
-                Ok(_) => (),
-                Err(e) => {
-                    eprintln!("重启进程失败: {}", e);
-                    std::process::exit(1);
-                }
+                eprintln!("{}", t!("main.config_or_target"));
+                std::process::exit(2);
             }
+            exit_on_error(
+                "main.restart_failed",
+                restart_process(config, Some(namespace), target, args),
+            );
+        }
+        Commands::Reload { target } => {
+            exit_on_error("main.reload_failed", reload_process(&target));
+        }
+        Commands::Describe { target } => {
+            exit_on_error("main.describe_failed", describe_process(&target));
+        }
+        Commands::Monit => {
+            exit_on_error("main.monit_failed", monit());
+        }
+        Commands::Schedule { command } => match command {
+            ScheduleCommands::List => exit_on_error("main.schedule_failed", list_schedules()),
+        },
+        Commands::Api { command } => match command {
+            ApiCommands::Serve => exit_on_error("main.api_failed", serve_api()),
+        },
+        Commands::Metrics { command } => {
+            let result = match command {
+                MetricsCommands::Serve { port, host } => serve_metrics(&host, port),
+                MetricsCommands::Show => print_metrics(),
+            };
+            exit_on_error("main.metrics_failed", result);
+        }
+        Commands::Watch { state, target } => {
+            exit_on_error(
+                "main.watch_failed",
+                set_watch(&target, matches!(state, WatchState::On)),
+            );
+        }
+        Commands::Daemon { interval } => {
+            exit_on_error(
+                "main.daemon_failed",
+                run_daemon(Duration::from_secs_f64(interval.max(0.5))),
+            );
+        }
+        Commands::Scale { name, count } => {
+            exit_on_error("main.scale_failed", scale_process(&name, &count));
         }
         Commands::Log { target } => {
-            if let Err(e) = tail_log(target) {
-                eprintln!("查看日志失败: {}", e);
-                std::process::exit(1);
-            }
+            exit_on_error("main.log_failed", tail_log(target));
+        }
+        Commands::Events {
+            target,
+            lines,
+            follow,
+            json,
+        } => {
+            exit_on_error(
+                "main.events_failed",
+                show_events(target.as_deref(), lines, follow, json),
+            );
         }
     }
+    flush_events();
 }
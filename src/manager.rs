@@ -0,0 +1,169 @@
+use super::base::event::flush_events;
+use super::base::process::PmrProcessInfo;
+use super::commands::list::{collect_processes, sample_system, ProcessEntry};
+use super::commands::restart::restart_existing_process;
+use super::commands::start::{launch_instances, start_existing_process, start_order, StartOptions};
+use super::commands::stop::{stop_and_notify, stop_order};
+use super::commands::target::{resolve_target, BatchResult};
+use super::config::dump::DumpConfig;
+use super::error::{PmrError, Result};
+use std::collections::BTreeMap;
+use std::env;
+
+/// 启动新进程的参数
+#[derive(Default)]
+pub struct ProcessSpec {
+    pub program: String,
+    pub args: Vec<String>,
+    pub name: Option<String>,      // 为空时使用程序名
+    pub namespace: Option<String>, // 为空时为 default
+    pub workdir: Option<String>,   // 为空时使用当前目录
+    pub env: BTreeMap<String, String>,
+    pub options: StartOptions,
+}
+
+impl ProcessSpec {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// 以编程方式管理进程，与命令行共用 ~/.pmr 下的进程列表。
+///
+/// ```no_run
+/// use pmr::{ProcessManager, ProcessSpec};
+///
+/// let manager = ProcessManager::new()?;
+/// let mut spec = ProcessSpec::new("python3");
+/// spec.args = vec!["-m".into(), "http.server".into()];
+/// spec.name = Some("web".into());
+/// manager.start(spec)?;
+/// manager.restart("web")?;
+/// manager.delete("web")?;
+/// # Ok::<(), pmr::PmrError>(())
+/// ```
+pub struct ProcessManager {
+    dump_config: &'static DumpConfig,
+}
+
+impl ProcessManager {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            dump_config: DumpConfig::try_get_instance().map_err(PmrError::Registry)?,
+        })
+    }
+
+    /// 所有进程及其实时指标
+    pub fn list(&self) -> Result<Vec<ProcessEntry>> {
+        self.dump_config.reload().map_err(PmrError::Registry)?;
+        collect_processes(&sample_system(), false)
+    }
+
+    /// 目标匹配的进程记录，目标可以是 pmr_id、名称、命名空间或 all
+    pub fn find(&self, target: &str) -> Result<Vec<PmrProcessInfo>> {
+        // 其他pmr命令可能修改了dump.json，每次操作前重新读取
+        self.dump_config.reload().map_err(PmrError::Registry)?;
+        let targets = resolve_target(
+            &self
+                .dump_config
+                .list_processes()
+                .map_err(PmrError::Registry)?,
+            target,
+        );
+        if targets.is_empty() {
+            return Err(PmrError::NotFound(target.to_string()));
+        }
+        Ok(targets)
+    }
+
+    /// 启动新程序，返回新进程的 pmr_id（集群模式下每个实例一个）
+    pub fn start(&self, spec: ProcessSpec) -> Result<Vec<u32>> {
+        if spec.program.trim().is_empty() {
+            return Err(PmrError::InvalidArgument(t!("start.program_empty")));
+        }
+        let name = spec.name.unwrap_or_else(|| {
+            let program = spec.program.as_str();
+            program
+                .split('/')
+                .next_back()
+                .unwrap_or(program)
+                .to_string()
+        });
+        let workdir = match spec.workdir {
+            Some(workdir) => workdir,
+            None => env::current_dir()?.to_string_lossy().to_string(),
+        };
+        let mut process = PmrProcessInfo::new(
+            name,
+            spec.namespace.unwrap_or_else(|| "default".to_string()),
+            workdir,
+            spec.program,
+            spec.args,
+        );
+        process.env = spec.env;
+        spec.options.apply(&mut process);
+
+        self.dump_config.reload().map_err(PmrError::Registry)?;
+        launch_instances(process, spec.options.instances)
+    }
+
+    /// 启动已存在的进程，被依赖的进程先启动，已经在运行的进程保持不变
+    pub fn start_existing(&self, target: &str) -> Result<Vec<u32>> {
+        let targets = start_order(self.find(target)?);
+        for_each(targets, |p| {
+            start_existing_process(p).map_err(|e| PmrError::start_failed(p, e))
+        })
+    }
+
+    /// 停止进程，依赖其他应用的进程先停止
+    pub fn stop(&self, target: &str) -> Result<Vec<u32>> {
+        let targets = stop_order(self.find(target)?);
+        for_each(targets, |p| {
+            stop_and_notify(p, "user").map_err(|e| PmrError::stop_failed(p, e))
+        })
+    }
+
+    pub fn restart(&self, target: &str) -> Result<Vec<u32>> {
+        let targets = self.find(target)?;
+        for_each(targets, |p| {
+            restart_existing_process(p, "manual")
+                .map(|_| ())
+                .map_err(|e| PmrError::restart_failed(p, e))
+        })
+    }
+
+    /// 停止并删除进程记录，日志文件会保留
+    pub fn delete(&self, target: &str) -> Result<Vec<u32>> {
+        let targets = stop_order(self.find(target)?);
+        for_each(targets, |p| {
+            if p.pid > 0 {
+                stop_and_notify(p, "deleted").map_err(|e| PmrError::delete_failed(p, e))?;
+            }
+            self.dump_config
+                .delete_process(p.pmr_id)
+                .map_err(|e| PmrError::delete_failed(p, e))
+        })
+    }
+}
+
+impl Drop for ProcessManager {
+    /// 等待后台发送的事件完成
+    fn drop(&mut self) {
+        flush_events();
+    }
+}
+
+/// 对每个进程执行操作，返回成功的 pmr_id；只有一个进程时直接返回它的错误
+fn for_each(
+    targets: Vec<PmrProcessInfo>,
+    action: impl Fn(&PmrProcessInfo) -> Result<()>,
+) -> Result<Vec<u32>> {
+    let mut result = BatchResult::default();
+    for process in &targets {
+        result.record(process, action(process));
+    }
+    result.into_result()
+}
@@ -0,0 +1,168 @@
+//! 运行 pmr 命令行，检查进程操作的输出和退出码
+
+use std::env;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn home(test: &str) -> PathBuf {
+    let home = env::temp_dir().join(format!("pmr-test-cli-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+    home
+}
+
+fn pmr(home: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pmr"))
+        .args(args)
+        .env("HOME", home)
+        .env("PMR_LANG", "en")
+        .current_dir(home)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn lifecycle_messages() {
+    let home = home("lifecycle");
+
+    let started = pmr(&home, &["start", "--name", "cli-app", "sleep", "--", "30"]);
+    assert!(started.status.success());
+    assert!(stdout(&started).contains("Started process 'cli-app' PID: "));
+
+    let again = pmr(&home, &["start", "cli-app"]);
+    assert!(stdout(&again).contains("Process 'cli-app' is already running"));
+
+    let restarted = pmr(&home, &["restart", "cli-app"]);
+    assert!(stdout(&restarted).contains("Process 'cli-app' restarted, new PID: "));
+
+    let stopped = pmr(&home, &["stop", "cli-app"]);
+    assert!(stopped.status.success());
+    assert!(stdout(&stopped).contains("Stopped process 'cli-app' (PID: "));
+
+    let stopped_again = pmr(&home, &["stop", "cli-app"]);
+    assert!(stdout(&stopped_again).contains("Process 'cli-app' is already stopped"));
+
+    let deleted = pmr(&home, &["delete", "cli-app"]);
+    assert!(deleted.status.success());
+    assert!(stdout(&deleted).contains("Deleted process 'cli-app'"));
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn batch_summary_and_missing_target() {
+    let home = home("batch");
+    for name in ["cli-a", "cli-b"] {
+        let output = pmr(&home, &["start", "--name", name, "sleep", "--", "30"]);
+        assert!(output.status.success());
+    }
+
+    let deleted = pmr(&home, &["delete", "all"]);
+    assert!(deleted.status.success());
+    assert!(stdout(&deleted).contains("Delete: 2 succeeded, 0 failed"));
+
+    // 找不到目标时退出码为 3
+    let missing = pmr(&home, &["stop", "cli-missing"]);
+    assert_eq!(missing.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("no process found: cli-missing"));
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn corrupt_registry_exit_code() {
+    let home = home("corrupt");
+    std::fs::create_dir_all(home.join(".pmr")).unwrap();
+    std::fs::write(home.join(".pmr").join("dump.json"), "garbage").unwrap();
+
+    // dump.json 无法读取时退出码为 5
+    let listed = pmr(&home, &["list"]);
+    assert_eq!(listed.status.code(), Some(5));
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn events_are_translated_when_shown() {
+    let home = home("events");
+    let started = pmr(
+        &home,
+        &["start", "--name", "cli-events", "sleep", "--", "30"],
+    );
+    assert!(started.status.success());
+    assert!(pmr(&home, &["stop", "cli-events"]).status.success());
+
+    // 事件日志只记录原因代码
+    let json = stdout(&pmr(&home, &["events", "cli-events", "--json"]));
+    assert!(json.contains("\"reason\":\"user\""));
+    assert!(!json.contains("stopped by user"));
+
+    let zh = Command::new(env!("CARGO_BIN_EXE_pmr"))
+        .args(["events", "cli-events"])
+        .env("HOME", &home)
+        .env("PMR_LANG", "zh")
+        .output()
+        .unwrap();
+    assert!(stdout(&zh).contains("用户停止"));
+    assert!(stdout(&pmr(&home, &["events", "cli-events"])).contains("stopped by user"));
+
+    pmr(&home, &["delete", "cli-events"]);
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn repeated_crashes_emit_crash_loop() {
+    let home = home("crash-loop");
+    let started = pmr(
+        &home,
+        &["start", "--name", "cli-crash", "sh", "--", "-c", "exit 1"],
+    );
+    assert!(started.status.success());
+
+    // 没有守护进程时，重启前发现进程已经退出同样计入崩溃循环
+    for _ in 0..5 {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        pmr(&home, &["restart", "cli-crash"]);
+    }
+
+    let json = stdout(&pmr(&home, &["events", "cli-crash", "--json"]));
+    let crash_loops: Vec<&str> = json
+        .lines()
+        .filter(|l| l.contains("\"event\":\"crash-loop\""))
+        .collect();
+    assert_eq!(crash_loops.len(), 1);
+    assert!(crash_loops[0].contains("\"reason\":\"exit\""));
+    assert!(crash_loops[0].contains("\"failures\":5"));
+
+    pmr(&home, &["delete", "cli-crash"]);
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn reload_rejects_ambiguous_readiness() {
+    let home = home("reload");
+    let started = pmr(
+        &home,
+        &[
+            "start",
+            "--name",
+            "cli-reload",
+            "--wait-ready",
+            "log:^ok",
+            "sh",
+            "--",
+            "-c",
+            "echo ok; sleep 30",
+        ],
+    );
+    assert!(started.status.success());
+
+    // 旧进程也会写入同一个日志，log 就绪信号无法区分新旧进程
+    let reloaded = pmr(&home, &["reload", "cli-reload"]);
+    assert!(!reloaded.status.success());
+    assert!(String::from_utf8_lossy(&reloaded.stderr)
+        .contains("cannot tell the new process from the old one"));
+
+    pmr(&home, &["delete", "cli-reload"]);
+    let _ = std::fs::remove_dir_all(&home);
+}
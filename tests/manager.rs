@@ -0,0 +1,148 @@
+//! 通过 ProcessManager 管理真实进程，进程列表和日志写入临时的 HOME 目录
+
+use pmr::{PmrError, ProcessManager, ProcessSpec};
+use std::env;
+use std::sync::Once;
+
+static INIT: Once = Once::new();
+
+// 进程列表在第一次使用时按 HOME 定位，所有测试共用同一个临时目录
+fn manager() -> ProcessManager {
+    INIT.call_once(|| {
+        let home = env::temp_dir().join(format!("pmr-test-manager-{}", std::process::id()));
+        std::fs::create_dir_all(&home).unwrap();
+        env::set_var("HOME", &home);
+        env::set_var("PMR_LANG", "en");
+    });
+    ProcessManager::new().unwrap()
+}
+
+fn sleep_spec(name: &str) -> ProcessSpec {
+    let mut spec = ProcessSpec::new("sleep");
+    spec.args = vec!["30".to_string()];
+    spec.name = Some(name.to_string());
+    spec.workdir = Some(env::temp_dir().to_string_lossy().to_string());
+    spec
+}
+
+#[test]
+fn start_stop_and_delete() {
+    let manager = manager();
+    let ids = manager.start(sleep_spec("manager-basic")).unwrap();
+    assert_eq!(ids.len(), 1);
+
+    let process = &manager.find("manager-basic").unwrap()[0];
+    assert_eq!(process.pmr_id, ids[0]);
+    assert_eq!(process.status, "running");
+    assert!(process.is_alive());
+    let listed = manager.list().unwrap();
+    assert!(listed.iter().any(|e| e.process.pmr_id == ids[0]));
+
+    assert_eq!(manager.stop("manager-basic").unwrap(), ids);
+    let stopped = &manager.find("manager-basic").unwrap()[0];
+    assert_eq!(stopped.status, "stopped");
+    assert_eq!(stopped.pid, 0);
+    assert!(!process.is_alive());
+
+    assert_eq!(manager.delete("manager-basic").unwrap(), ids);
+    assert!(matches!(
+        manager.find("manager-basic"),
+        Err(PmrError::NotFound(_))
+    ));
+}
+
+#[test]
+fn restart_replaces_the_process() {
+    let manager = manager();
+    manager.start(sleep_spec("manager-restart")).unwrap();
+    let before = manager.find("manager-restart").unwrap().remove(0);
+
+    manager.restart("manager-restart").unwrap();
+    let after = manager.find("manager-restart").unwrap().remove(0);
+    assert_ne!(after.pid, before.pid);
+    assert!(after.is_alive());
+    assert!(!before.is_alive());
+    assert_eq!(after.restarts, 1);
+    assert_eq!(after.restart_history[0].reason, "manual");
+
+    manager.delete("manager-restart").unwrap();
+}
+
+#[test]
+fn start_existing_keeps_running_processes() {
+    let manager = manager();
+    manager.start(sleep_spec("manager-existing")).unwrap();
+    let running = manager.find("manager-existing").unwrap().remove(0);
+
+    // 已经在运行的进程保持不变
+    manager.start_existing("manager-existing").unwrap();
+    let unchanged = manager.find("manager-existing").unwrap().remove(0);
+    assert_eq!(unchanged.pid, running.pid);
+
+    manager.stop("manager-existing").unwrap();
+    manager.start_existing("manager-existing").unwrap();
+    let restarted = manager.find("manager-existing").unwrap().remove(0);
+    assert_eq!(restarted.status, "running");
+    assert!(restarted.is_alive());
+    assert_ne!(restarted.pid, running.pid);
+
+    manager.delete("manager-existing").unwrap();
+}
+
+#[test]
+fn cluster_instances_share_a_name() {
+    let manager = manager();
+    let mut spec = sleep_spec("manager-cluster");
+    spec.namespace = Some("manager-cluster-ns".to_string());
+    spec.options.instances = Some(2);
+    let ids = manager.start(spec).unwrap();
+    assert_eq!(ids.len(), 2);
+
+    let mut instances: Vec<Option<u32>> = manager
+        .find("manager-cluster")
+        .unwrap()
+        .iter()
+        .map(|p| p.instance_id)
+        .collect();
+    instances.sort();
+    assert_eq!(instances, vec![Some(0), Some(1)]);
+
+    // 按命名空间删除同一组的所有实例
+    let mut deleted = manager.delete("manager-cluster-ns").unwrap();
+    deleted.sort();
+    assert_eq!(deleted, ids);
+    assert!(manager.find("manager-cluster").is_err());
+}
+
+#[test]
+fn invalid_requests_are_rejected() {
+    let manager = manager();
+    assert!(matches!(
+        manager.start(ProcessSpec::new(" ")),
+        Err(PmrError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        manager.stop("manager-missing"),
+        Err(PmrError::NotFound(_))
+    ));
+
+    let mut spec = sleep_spec("manager-missing-program");
+    spec.program = "/nonexistent/pmr-test".to_string();
+    assert!(matches!(
+        manager.start(spec),
+        Err(PmrError::StartFailed { .. })
+    ));
+
+    // pre_start 钩子失败时同样不启动
+    let mut spec = sleep_spec("manager-failed-hook");
+    spec.options.pre_start = Some("exit 1".to_string());
+    assert!(matches!(
+        manager.start(spec),
+        Err(PmrError::StartFailed { .. })
+    ));
+
+    // 启动失败的进程不会留下记录
+    for name in ["manager-missing-program", "manager-failed-hook"] {
+        assert!(matches!(manager.find(name), Err(PmrError::NotFound(_))));
+    }
+}